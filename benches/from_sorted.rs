@@ -0,0 +1,42 @@
+//! compares building a trie from sorted input in one pass against inserting the same elements one
+//! by one, run with `cargo bench --bench from_sorted`
+
+use std::time::{Duration, Instant};
+
+use triez::{AsciiLower, Trie};
+
+/// `count` pseudo random words of 3 to 14 letters, sorted, the letters skewed towards the start of
+/// the alphabet such that words share prefixes as those of a natural language would
+fn dictionary(count: usize) -> Vec<String> {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = move |bound: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % bound
+    };
+    let mut words = (0..count).map(|_| {
+        let len = 3 + next(12);
+        (0..len).map(|_| (b'a' + (next(26) * next(26) / 26) as u8) as char).collect::<String>()
+    }).collect::<Vec<_>>();
+    words.sort();
+    words
+}
+
+fn time<F: FnMut() -> usize>(mut f: F) -> Duration {
+    let rounds = 10;
+    let start = Instant::now();
+    for _ in 0..rounds {
+        assert!(f() > 0);
+    }
+    start.elapsed() / rounds
+}
+
+fn main() {
+    let words = dictionary(200_000);
+    let naive = time(|| Trie::from_iter_with(AsciiLower, words.iter().map(String::as_str)).len());
+    let sorted = time(|| Trie::from_sorted_iter(AsciiLower, words.iter().map(String::as_str)).len());
+    println!("{} words", words.len());
+    println!("insert one by one: {:?}", naive);
+    println!("from_sorted_iter:  {:?}", sorted);
+}
@@ -0,0 +1,40 @@
+//! the throughput of inserting words one by one, which recurses over a single iterator of parts
+//! rather than keeping a stack of two kinds of iterators, run with `cargo bench --bench insert`
+
+use std::time::{Duration, Instant};
+
+use triez::{AsciiLower, Trie};
+
+/// `count` distinct lowercase words, sharing prefixes as words of a language do
+fn words(count: usize) -> Vec<String> {
+    (0..count).map(|i| {
+        let mut i = i * 2654435761 % (1 << 32);
+        let mut word = String::new();
+        for _ in 0..10 {
+            word.push((b'a' + (i % 26) as u8) as char);
+            i /= 7;
+        }
+        word
+    }).collect()
+}
+
+/// the fastest of a few rounds of inserting `words` into an empty trie, building the trie included
+fn insert(words: &[String]) -> Duration {
+    (0..7).map(|_| {
+        let start = Instant::now();
+        let mut trie = Trie::new(AsciiLower);
+        for word in words {
+            trie.insert(word.as_str());
+        }
+        let elapsed = start.elapsed();
+        assert_eq!(trie.len(), words.len());
+        elapsed
+    }).min().unwrap()
+}
+
+fn main() {
+    let words = words(100_000);
+    let elapsed = insert(&words);
+    println!("{} words", words.len());
+    println!("insert: {:?}, {:.0} words/s", elapsed, words.len() as f64 / elapsed.as_secs_f64());
+}
@@ -0,0 +1,66 @@
+//! counts the reallocations made while inserting long keys, which should be none past the first
+//! allocation of every run, run with `cargo bench --bench long_keys`
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use triez::{Trie, Utf8};
+
+/// the system allocator, counting its calls
+struct Counting;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+static REALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        REALLOCS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// `count` distinct keys of `len` bytes each
+fn keys(count: usize, len: usize) -> Vec<String> {
+    (0..count).map(|i| format!("{:08}", i).repeat(len / 8)).collect()
+}
+
+/// inserts every key, returns the allocations and reallocations made along with the time taken
+fn measure<F: FnOnce() -> usize>(f: F) -> (usize, usize, std::time::Duration) {
+    let allocs = ALLOCS.load(Ordering::Relaxed);
+    let reallocs = REALLOCS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    assert!(f() > 0);
+    let elapsed = start.elapsed();
+    (ALLOCS.load(Ordering::Relaxed) - allocs, REALLOCS.load(Ordering::Relaxed) - reallocs, elapsed)
+}
+
+fn main() {
+    let count = 10_000;
+    let len = 4096;
+    let bytes = measure(|| {
+        let mut trie = Trie::bytes();
+        trie.extend(keys(count, len));
+        trie.len()
+    });
+    let chars = measure(|| {
+        let mut trie = Trie::new(Utf8);
+        trie.extend(keys(count, len));
+        trie.len()
+    });
+    println!("{} keys of {} bytes", count, len);
+    println!("bytes, from Vec<u8>: {} allocations, {} reallocations, {:?}", bytes.0, bytes.1, bytes.2);
+    println!("chars, from String:  {} allocations, {} reallocations, {:?}", chars.0, chars.1, chars.2);
+}
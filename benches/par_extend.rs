@@ -0,0 +1,43 @@
+//! compares building a trie with `par_extend` against inserting the same elements one by one,
+//! run with `cargo bench --bench par_extend --features rayon`
+
+use std::time::{Duration, Instant};
+
+use triez::{Bytes, Trie};
+
+/// `count` pseudo random keys of 8 to 39 bytes, spread over every first byte
+fn keys(count: usize) -> Vec<Vec<u8>> {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    (0..count).map(|_| {
+        let len = 8 + (next() % 32) as usize;
+        (0..len).map(|_| next() as u8).collect()
+    }).collect()
+}
+
+fn time<F: FnMut() -> usize>(mut f: F) -> Duration {
+    let rounds = 5;
+    let start = Instant::now();
+    for _ in 0..rounds {
+        assert!(f() > 0);
+    }
+    start.elapsed() / rounds
+}
+
+fn main() {
+    let keys = keys(1_000_000);
+    let sequential = Trie::from_iter_with(Bytes, keys.iter().map(Vec::as_slice));
+    let parallel = Trie::from_par_iter_with(Bytes, keys.iter().map(Vec::as_slice).collect::<Vec<_>>());
+    assert!(sequential == parallel);
+
+    let naive = time(|| Trie::from_iter_with(Bytes, keys.iter().map(Vec::as_slice)).len());
+    let par = time(|| Trie::from_par_iter_with(Bytes, keys.iter().map(Vec::as_slice).collect::<Vec<_>>()).len());
+    println!("{} keys on {} threads", keys.len(), rayon::current_num_threads());
+    println!("insert one by one: {:?}", naive);
+    println!("par_extend:        {:?}", par);
+}
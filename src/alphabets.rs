@@ -0,0 +1,119 @@
+use super::Alphabet;
+
+/// the 26 letters of the latin alphabet, ignoring case
+///
+/// any other `char` is out of the alphabet
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AsciiLower;
+
+impl Alphabet<char> for AsciiLower {
+    fn index(&self, part: &char) -> usize {
+        (part.to_ascii_lowercase() as usize).wrapping_sub('a' as usize)
+    }
+
+    fn size(&self) -> usize {
+        26
+    }
+}
+
+/// every value of a byte
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Bytes;
+
+impl Alphabet<u8> for Bytes {
+    fn index(&self, part: &u8) -> usize {
+        *part as usize
+    }
+
+    fn size(&self) -> usize {
+        256
+    }
+}
+
+/// the two values of a bit, `false` before `true`, see `Bits`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Binary;
+
+impl Alphabet<bool> for Binary {
+    fn index(&self, part: &bool) -> usize {
+        *part as usize
+    }
+
+    fn size(&self) -> usize {
+        2
+    }
+}
+
+/// every unicode scalar value, for the `char`s of any string
+///
+/// wide enough that branching nodes only ever hold their occupied positions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Utf8;
+
+impl Alphabet<char> for Utf8 {
+    fn index(&self, part: &char) -> usize {
+        *part as usize
+    }
+
+    fn size(&self) -> usize {
+        char::MAX as usize + 1
+    }
+}
+
+/// an alphabet of `size` symbols given by an index function, see `Trie::from_fn`
+#[derive(Debug, Clone, Copy)]
+pub struct FnAlphabet<F> {
+    index_fn: F,
+    size: usize,
+}
+
+impl<F> FnAlphabet<F> {
+    /// `size` is a count rather than the largest index, `index_fn` must map every part into
+    /// `0..size`
+    pub fn new(index_fn: F, size: usize) -> FnAlphabet<F> {
+        FnAlphabet { index_fn, size }
+    }
+}
+
+impl<TParts, F: Fn(&TParts) -> usize> Alphabet<TParts> for FnAlphabet<F> {
+    fn index(&self, part: &TParts) -> usize {
+        (self.index_fn)(part)
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+}
+
+/// an alphabet of `size` symbols given by an index function, which returns `None` for parts to
+/// skip, see `Trie::from_skipping_fn`
+#[derive(Debug, Clone, Copy)]
+pub struct SkipFnAlphabet<F> {
+    index_fn: F,
+    size: usize,
+}
+
+impl<F> SkipFnAlphabet<F> {
+    /// `size` is a count rather than the largest index, `index_fn` must map every part it does not
+    /// skip into `0..size`
+    pub fn new(index_fn: F, size: usize) -> SkipFnAlphabet<F> {
+        SkipFnAlphabet { index_fn, size }
+    }
+}
+
+impl<TParts, F: Fn(&TParts) -> Option<usize>> Alphabet<TParts> for SkipFnAlphabet<F> {
+    const SKIPS: bool = true;
+
+    /// skipped parts are never indexed, they would be out of range
+    fn index(&self, part: &TParts) -> usize {
+        (self.index_fn)(part).unwrap_or(self.size)
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn skips(&self, part: &TParts) -> bool {
+        (self.index_fn)(part).is_none()
+    }
+}
@@ -0,0 +1,213 @@
+use alloc::collections::btree_map;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::iter::Enumerate;
+use core::mem;
+use core::slice;
+
+use super::radix_tree::Node;
+
+/// alphabets larger than this get sparse children, keeping only the occupied positions
+pub(crate) const SPARSE_ALPHABET_SIZE: usize = 32;
+
+/// a position of a dense `Normal` node, holding the part leading to the child if occupied
+type Slot<T, V> = Option<(T, Node<T, V>)>;
+
+/// the children of a `Normal` node, each held along with the part leading to it
+///
+/// `Dense` allocates a slot for every symbol of the alphabet up front, which is cheapest to index
+/// for small alphabets. `Sparse` only holds occupied positions, ordered by index, such that wide
+/// alphabets (bytes, unicode ranges) do not pay for every `Normal` node being mostly empty.
+/// `Dense` counts its occupied slots as they are filled and emptied, such that the collapsing of
+/// nodes left with a single child does not scan every slot.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SerializedChildren<T, V>"))]
+pub(crate) enum Children<T, V> {
+    Dense { slots: Vec<Slot<T, V>>, occupied: usize },
+    Sparse(BTreeMap<usize, (T, Node<T, V>)>),
+}
+
+/// deserialized children, checked before being taken as `Children`
+///
+/// a `Dense` count of occupied slots not matching its slots is rejected. the number of slots is
+/// checked against the alphabet by `Trie::from_serialized`, the alphabet being unknown here
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "Children")]
+enum SerializedChildren<T, V> {
+    Dense { slots: Vec<Slot<T, V>>, occupied: usize },
+    Sparse(BTreeMap<usize, (T, Node<T, V>)>),
+}
+
+#[cfg(feature = "serde")]
+impl<T, V> core::convert::TryFrom<SerializedChildren<T, V>> for Children<T, V> {
+    type Error = alloc::string::String;
+
+    fn try_from(children: SerializedChildren<T, V>) -> Result<Children<T, V>, alloc::string::String> {
+        match children {
+            SerializedChildren::Dense { slots, occupied } => {
+                let counted = slots.iter().filter(|slot| slot.is_some()).count();
+                if counted != occupied {
+                    return Err(alloc::format!("dense children count {} occupied slots out of {}", occupied, counted));
+                }
+                Ok(Children::Dense { slots, occupied })
+            }
+            SerializedChildren::Sparse(children) => Ok(Children::Sparse(children)),
+        }
+    }
+}
+
+impl<T, V> Children<T, V> {
+    pub(crate) fn new(alphabet_size: usize) -> Children<T, V> {
+        if alphabet_size > SPARSE_ALPHABET_SIZE {
+            Children::Sparse(BTreeMap::new())
+        } else {
+            Children::new_dense(alphabet_size)
+        }
+    }
+
+    pub(crate) fn new_dense(alphabet_size: usize) -> Children<T, V> {
+        let mut slots = Vec::with_capacity(alphabet_size);
+        for _ in 0..alphabet_size {
+            slots.push(None);
+        }
+        Children::Dense { slots, occupied: 0 }
+    }
+
+    pub(crate) fn get(&self, pos: usize) -> Option<&(T, Node<T, V>)> {
+        match self {
+            Children::Dense { slots, .. } => slots.get(pos).and_then(Option::as_ref),
+            Children::Sparse(children) => children.get(&pos),
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self, pos: usize) -> Option<&mut (T, Node<T, V>)> {
+        match self {
+            Children::Dense { slots, .. } => slots.get_mut(pos).and_then(Option::as_mut),
+            Children::Sparse(children) => children.get_mut(&pos),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, pos: usize, part: T, node: Node<T, V>) {
+        match self {
+            Children::Dense { slots, occupied } => {
+                if slots[pos].replace((part, node)).is_none() {
+                    *occupied += 1;
+                }
+            }
+            Children::Sparse(children) => {
+                children.insert(pos, (part, node));
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, pos: usize) -> Option<(T, Node<T, V>)> {
+        match self {
+            Children::Dense { slots, occupied } => {
+                let removed = slots.get_mut(pos).and_then(Option::take);
+                if removed.is_some() {
+                    *occupied -= 1;
+                }
+                removed
+            }
+            Children::Sparse(children) => children.remove(&pos),
+        }
+    }
+
+    /// removes and returns the child with the lowest index
+    pub(crate) fn take_first(&mut self) -> Option<(T, Node<T, V>)> {
+        match self {
+            Children::Dense { slots, occupied } => {
+                let taken = slots.iter_mut().find(|c| c.is_some()).and_then(Option::take);
+                if taken.is_some() {
+                    *occupied -= 1;
+                }
+                taken
+            }
+            Children::Sparse(children) => {
+                let pos = *children.keys().next()?;
+                children.remove(&pos)
+            }
+        }
+    }
+
+    /// number of occupied positions
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Children::Dense { occupied, .. } => *occupied,
+            Children::Sparse(children) => children.len(),
+        }
+    }
+
+    /// switches dense children with at most a quarter of their slots occupied to the sparse
+    /// representation, which only holds the occupied positions
+    pub(crate) fn shrink_to_fit(&mut self) {
+        let sparse = match self {
+            Children::Dense { slots, occupied } if *occupied * 4 <= slots.len() => {
+                mem::take(slots).into_iter().enumerate().filter_map(|(pos, slot)| Some((pos, slot?))).collect()
+            }
+            _ => return,
+        };
+        *self = Children::Sparse(sparse);
+    }
+
+    /// bytes allocated for the slots, without what the children allocate themselves
+    ///
+    /// sparse children are counted as their entries alone, ignoring the spare room and bookkeeping
+    /// of the nodes of the map
+    pub(crate) fn heap_size(&self) -> usize {
+        match self {
+            Children::Dense { slots, .. } => slots.capacity() * mem::size_of::<Slot<T, V>>(),
+            Children::Sparse(children) => children.len() * mem::size_of::<(usize, (T, Node<T, V>))>(),
+        }
+    }
+
+    /// the parts and children of the occupied positions, by ascending index
+    pub(crate) fn into_vec(self) -> Vec<(T, Node<T, V>)> {
+        match self {
+            Children::Dense { slots, .. } => slots.into_iter().flatten().collect(),
+            Children::Sparse(children) => children.into_values().collect(),
+        }
+    }
+
+    /// occupied positions along with their part and child, by ascending index
+    pub(crate) fn iter(&self) -> Iter<'_, T, V> {
+        match self {
+            Children::Dense { slots, .. } => Iter::Dense(slots.iter().enumerate()),
+            Children::Sparse(children) => Iter::Sparse(children.iter()),
+        }
+    }
+}
+
+pub(crate) enum Iter<'a, T, V> {
+    Dense(Enumerate<slice::Iter<'a, Slot<T, V>>>),
+    Sparse(btree_map::Iter<'a, usize, (T, Node<T, V>)>),
+}
+
+impl<'a, T, V> Iterator for Iter<'a, T, V> {
+    type Item = (usize, &'a T, &'a Node<T, V>);
+
+    fn next(&mut self) -> Option<(usize, &'a T, &'a Node<T, V>)> {
+        match self {
+            Iter::Dense(it) => it.find_map(|(pos, c)| c.as_ref().map(|(part, node)| (pos, part, node))),
+            Iter::Sparse(it) => it.next().map(|(pos, (part, node))| (*pos, part, node)),
+        }
+    }
+}
+
+impl<'a, T, V> DoubleEndedIterator for Iter<'a, T, V> {
+    fn next_back(&mut self) -> Option<(usize, &'a T, &'a Node<T, V>)> {
+        match self {
+            Iter::Dense(it) => {
+                while let Some((pos, c)) = it.next_back() {
+                    if let Some((part, node)) = c {
+                        return Some((pos, part, node));
+                    }
+                }
+                None
+            }
+            Iter::Sparse(it) => it.next_back().map(|(pos, (part, node))| (*pos, part, node)),
+        }
+    }
+}
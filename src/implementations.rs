@@ -1,17 +1,287 @@
-use super::Decomposable;
+use alloc::string::String;
+use alloc::vec::Vec;
 
-impl Decomposable<char, std::vec::IntoIter<char>> for String {
-    fn decompose(self) -> std::vec::IntoIter<char> {
-        self.chars().collect::<Vec<_>>().into_iter()
+use super::{Decomposable, DecomposableRef, DecomposeRef};
+
+/// lazy iterator over the `char`s of an owned `String`
+pub struct IntoChars {
+    string: String,
+    position: usize,
+}
+
+impl Iterator for IntoChars {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.string[self.position..].chars().next()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    /// every `char` is one to four bytes of what is left
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.string.len() - self.position;
+        (remaining.div_ceil(4), Some(remaining))
+    }
+}
+
+impl Decomposable<char> for String {
+    type Iterator = IntoChars;
+
+    fn decompose(self) -> IntoChars {
+        IntoChars { string: self, position: 0 }
+    }
+}
+
+impl<'a> Decomposable<char> for &'a str {
+    type Iterator = core::str::Chars<'a>;
+
+    fn decompose(self) -> core::str::Chars<'a> {
+        self.chars()
+    }
+}
+
+/// a single `char` as a key of its own
+impl Decomposable<char> for char {
+    type Iterator = core::iter::Once<char>;
+
+    fn decompose(self) -> core::iter::Once<char> {
+        core::iter::once(self)
+    }
+}
+
+impl Decomposable<char> for &char {
+    type Iterator = core::iter::Once<char>;
+
+    fn decompose(self) -> core::iter::Once<char> {
+        core::iter::once(*self)
+    }
+}
+
+impl Decomposable<u8> for String {
+    type Iterator = alloc::vec::IntoIter<u8>;
+
+    fn decompose(self) -> alloc::vec::IntoIter<u8> {
+        self.into_bytes().into_iter()
+    }
+}
+
+impl<'a> Decomposable<u8> for &'a str {
+    type Iterator = core::str::Bytes<'a>;
+
+    fn decompose(self) -> core::str::Bytes<'a> {
+        self.bytes()
+    }
+}
+
+impl<'a> DecomposableRef<'a, char> for String {
+    type Iterator = core::str::Chars<'a>;
+
+    fn decompose_ref(&'a self) -> core::str::Chars<'a> {
+        self.chars()
+    }
+}
+
+/// lowercases the `char`s of `S` as it is decomposed, such that the lowercase form is what the
+/// trie stores and reconstructs, whatever the case it was inserted or queried with
+///
+/// the full unicode lowercase mapping is used, so a `char` may become several parts, `'İ'` is
+/// stored as `'i'` followed by a combining dot above. wrap queries as well as insertions for
+/// anything not already lowercase to be found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CaseInsensitive<S>(pub S);
+
+impl<S: Decomposable<char>> Decomposable<char> for CaseInsensitive<S> {
+    type Iterator = core::iter::FlatMap<S::Iterator, core::char::ToLowercase, fn(char) -> core::char::ToLowercase>;
+
+    fn decompose(self) -> Self::Iterator {
+        self.0.decompose().flat_map(char::to_lowercase as fn(char) -> core::char::ToLowercase)
+    }
+}
+
+/// splits the string `S` into its extended grapheme clusters, such that what reads as a single
+/// character is a single part, combining marks included
+///
+/// clusters are compared as they are written, a precomposed `"é"` and an `'e'` followed by a
+/// combining acute accent are different parts although they read the same. strings are expected to
+/// be normalized before being wrapped, such as to NFC with the `unicode-normalization` crate, for
+/// such spellings to be found. the parts are strings, the trie needs an alphabet indexing every
+/// cluster it is to hold
+#[cfg(feature = "unicode-segmentation")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GraphemeString<S>(pub S);
+
+#[cfg(feature = "unicode-segmentation")]
+impl<S: AsRef<str>> Decomposable<String> for GraphemeString<S> {
+    type Iterator = alloc::vec::IntoIter<String>;
+
+    fn decompose(self) -> alloc::vec::IntoIter<String> {
+        use unicode_segmentation::UnicodeSegmentation;
+        self.0.as_ref().graphemes(true).map(String::from).collect::<Vec<_>>().into_iter()
+    }
+}
+
+#[cfg(feature = "unicode-segmentation")]
+impl<'a> Decomposable<&'a str> for GraphemeString<&'a str> {
+    type Iterator = unicode_segmentation::Graphemes<'a>;
+
+    fn decompose(self) -> unicode_segmentation::Graphemes<'a> {
+        unicode_segmentation::UnicodeSegmentation::graphemes(self.0, true)
+    }
+}
+
+impl<T> Decomposable<T> for Vec<T> {
+    type Iterator = alloc::vec::IntoIter<T>;
+
+    fn decompose(self) -> alloc::vec::IntoIter<T> {
+        self.into_iter()
+    }
+}
+
+/// clones the parts one at a time as the slice is walked, without collecting them, a copy for the
+/// bytes of binary keys
+impl<'a, T: Clone> Decomposable<T> for &'a [T] {
+    type Iterator = core::iter::Cloned<core::slice::Iter<'a, T>>;
+
+    fn decompose(self) -> core::iter::Cloned<core::slice::Iter<'a, T>> {
+        self.iter().cloned()
+    }
+}
+
+// `OsStr` is only plain bytes on unix, on windows it is WTF-8 internally and exposed as wide
+// characters, so these are not provided there rather than decomposing lossily. like the path and
+// address impls below they need the `std` feature
+#[cfg(all(unix, feature = "std"))]
+impl Decomposable<u8> for std::ffi::OsString {
+    type Iterator = alloc::vec::IntoIter<u8>;
+
+    fn decompose(self) -> alloc::vec::IntoIter<u8> {
+        std::os::unix::ffi::OsStringExt::into_vec(self).into_iter()
+    }
+}
+
+#[cfg(all(unix, feature = "std"))]
+impl<'a> Decomposable<u8> for &'a std::ffi::OsStr {
+    type Iterator = core::iter::Copied<core::slice::Iter<'a, u8>>;
+
+    fn decompose(self) -> core::iter::Copied<core::slice::Iter<'a, u8>> {
+        std::os::unix::ffi::OsStrExt::as_bytes(self).iter().copied()
+    }
+}
+
+/// an owned `std::path::Component`, the part `PathBuf`s are decomposed into
+///
+/// `Component` borrows from the path it was split from, these own their names such that the
+/// decomposition can outlive the consumed `PathBuf`
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum OwnedComponent {
+    Prefix(std::ffi::OsString),
+    RootDir,
+    CurDir,
+    ParentDir,
+    Normal(std::ffi::OsString),
+}
+
+#[cfg(feature = "std")]
+impl<'a> From<std::path::Component<'a>> for OwnedComponent {
+    fn from(component: std::path::Component<'a>) -> OwnedComponent {
+        match component {
+            std::path::Component::Prefix(prefix) => OwnedComponent::Prefix(prefix.as_os_str().to_owned()),
+            std::path::Component::RootDir => OwnedComponent::RootDir,
+            std::path::Component::CurDir => OwnedComponent::CurDir,
+            std::path::Component::ParentDir => OwnedComponent::ParentDir,
+            std::path::Component::Normal(name) => OwnedComponent::Normal(name.to_owned()),
+        }
+    }
+}
+
+/// decomposes a path into its components, such that paths share nodes per directory rather than
+/// per byte. the alphabet is left to the user, mapping every component that may occur into its
+/// symbols
+#[cfg(feature = "std")]
+impl Decomposable<OwnedComponent> for std::path::PathBuf {
+    type Iterator = alloc::vec::IntoIter<OwnedComponent>;
+
+    fn decompose(self) -> alloc::vec::IntoIter<OwnedComponent> {
+        self.components().map(OwnedComponent::from).collect::<Vec<_>>().into_iter()
+    }
+}
+
+impl<const N: usize> Decomposable<u8> for [u8; N] {
+    type Iterator = core::array::IntoIter<u8, N>;
+
+    fn decompose(self) -> core::array::IntoIter<u8, N> {
+        IntoIterator::into_iter(self)
+    }
+}
+
+/// byte string literals, `b"GET"` being a `&[u8; 3]`
+impl<'a, const N: usize> Decomposable<u8> for &'a [u8; N] {
+    type Iterator = core::iter::Copied<core::slice::Iter<'a, u8>>;
+
+    fn decompose(self) -> core::iter::Copied<core::slice::Iter<'a, u8>> {
+        self.iter().copied()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Decomposable<u8> for std::net::Ipv4Addr {
+    type Iterator = core::array::IntoIter<u8, 4>;
+
+    fn decompose(self) -> core::array::IntoIter<u8, 4> {
+        IntoIterator::into_iter(self.octets())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Decomposable<u8> for std::net::Ipv6Addr {
+    type Iterator = core::array::IntoIter<u8, 16>;
+
+    fn decompose(self) -> core::array::IntoIter<u8, 16> {
+        IntoIterator::into_iter(self.octets())
+    }
+}
+
+impl<'a, T: 'a> DecomposeRef<'a, T> for [T] {
+    type Iterator = core::slice::Iter<'a, T>;
+
+    fn parts(&'a self) -> core::slice::Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T: 'a> DecomposeRef<'a, T> for Vec<T> {
+    type Iterator = core::slice::Iter<'a, T>;
+
+    fn parts(&'a self) -> core::slice::Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T: 'a, const N: usize> DecomposeRef<'a, T> for [T; N] {
+    type Iterator = core::slice::Iter<'a, T>;
+
+    fn parts(&'a self) -> core::slice::Iter<'a, T> {
+        self.iter()
     }
 }
 
 macro_rules! impl_decomposable_for_integer {
     ( $t:ty ) => {
-        impl Decomposable<u8, std::vec::IntoIter<u8>> for $t {
-            fn decompose(self) -> std::vec::IntoIter<u8> {
-                let bytes : Box<[u8]> = Box::new(self.to_be_bytes());
-                bytes.into_vec().into_iter()
+        impl Decomposable<u8> for $t {
+            type Iterator = core::array::IntoIter<u8, { core::mem::size_of::<$t>() }>;
+
+            fn decompose(self) -> core::array::IntoIter<u8, { core::mem::size_of::<$t>() }> {
+                IntoIterator::into_iter(self.to_be_bytes())
+            }
+        }
+
+        impl<'a> Decomposable<u8> for &'a $t {
+            type Iterator = core::array::IntoIter<u8, { core::mem::size_of::<$t>() }>;
+
+            fn decompose(self) -> core::array::IntoIter<u8, { core::mem::size_of::<$t>() }> {
+                (*self).decompose()
             }
         }
     };
@@ -19,20 +289,136 @@ macro_rules! impl_decomposable_for_integer {
 
 macro_rules! impl_decomposable_for_float {
     ( $t:ty ) => {
-        impl Decomposable<u8, std::vec::IntoIter<u8>> for $t {
-            fn decompose(self) -> std::vec::IntoIter<u8> {
-                let bytes : Box<[u8]> = Box::new(self.to_bits().to_be_bytes());
-                bytes.into_vec().into_iter()
+        impl Decomposable<u8> for $t {
+            type Iterator = core::array::IntoIter<u8, { core::mem::size_of::<$t>() }>;
+
+            fn decompose(self) -> core::array::IntoIter<u8, { core::mem::size_of::<$t>() }> {
+                IntoIterator::into_iter(self.to_bits().to_be_bytes())
+            }
+        }
+
+        impl<'a> Decomposable<u8> for &'a $t {
+            type Iterator = core::array::IntoIter<u8, { core::mem::size_of::<$t>() }>;
+
+            fn decompose(self) -> core::array::IntoIter<u8, { core::mem::size_of::<$t>() }> {
+                (*self).decompose()
+            }
+        }
+    };
+}
+
+/// decomposes a signed integer or float into big endian bytes ordered the same as the numbers
+///
+/// the bytes of a plain signed integer order negative numbers after positive ones, as their top
+/// byte is the highest, those of a plain float also order negative floats in reverse. integers
+/// have their sign bit flipped, floats have every bit flipped if negative and only the sign bit
+/// otherwise. wrap every insertion and query the same for `range` and ordered iteration over
+/// signed numbers to be numeric
+///
+/// floats follow the total order of `f64::total_cmp`: `-0.0` is a distinct element ordered before
+/// `0.0`, NaNs are stored with their bits, those with the sign bit set before negative infinity and
+/// the others after positive infinity. NaNs with different payloads are different elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SignOrdered<I>(pub I);
+
+macro_rules! impl_decomposable_for_sign_ordered {
+    ( $t:ty ) => {
+        impl Decomposable<u8> for SignOrdered<$t> {
+            type Iterator = core::array::IntoIter<u8, { core::mem::size_of::<$t>() }>;
+
+            fn decompose(self) -> core::array::IntoIter<u8, { core::mem::size_of::<$t>() }> {
+                IntoIterator::into_iter((self.0 ^ <$t>::MIN).to_be_bytes())
             }
         }
     };
 }
 
+macro_rules! impl_decomposable_for_sign_ordered_float {
+    ( $t:ty, $bits:ty ) => {
+        impl Decomposable<u8> for SignOrdered<$t> {
+            type Iterator = core::array::IntoIter<u8, { core::mem::size_of::<$t>() }>;
+
+            fn decompose(self) -> core::array::IntoIter<u8, { core::mem::size_of::<$t>() }> {
+                let bits = self.0.to_bits();
+                let sign = !(<$bits>::MAX >> 1);
+                let flipped = if bits & sign != 0 { !bits } else { bits ^ sign };
+                IntoIterator::into_iter(flipped.to_be_bytes())
+            }
+        }
+    };
+}
+
+/// decomposes an unsigned integer into its bits as `bool`s, most significant first, for binary
+/// tries branching on a bit at a time over the `Binary` alphabet
+///
+/// `Bits::prefix` keeps only the leading bits, such as the network bits of a CIDR block, which
+/// `longest_prefix_match` then matches addresses against bit by bit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bits<I> {
+    value: I,
+    len: u32,
+}
+
+impl<I> Bits<I> {
+    /// every bit of `value`
+    pub fn new(value: I) -> Bits<I> {
+        Bits { value, len: core::mem::size_of::<I>() as u32 * 8 }
+    }
+
+    /// the `len` most significant bits of `value`
+    ///
+    /// # Panics
+    ///
+    /// panics if `len` is more than the bits of `I`
+    pub fn prefix(value: I, len: u32) -> Bits<I> {
+        let width = core::mem::size_of::<I>() as u32 * 8;
+        assert!(len <= width, "prefix of {} bits is longer than the {} bits of the integer", len, width);
+        Bits { value, len }
+    }
+}
+
+/// the bits of a `Bits`, most significant first
+#[derive(Debug, Clone)]
+pub struct IntoBits {
+    value: u128,
+    width: u32,
+    next: u32,
+    len: u32,
+}
+
+impl Iterator for IntoBits {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.next == self.len {
+            return None;
+        }
+        self.next += 1;
+        Some((self.value >> (self.width - self.next)) & 1 == 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.len - self.next) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<I: Into<u128>> Decomposable<bool> for Bits<I> {
+    type Iterator = IntoBits;
+
+    fn decompose(self) -> IntoBits {
+        let width = core::mem::size_of::<I>() as u32 * 8;
+        IntoBits { value: self.value.into(), width, next: 0, len: self.len }
+    }
+}
+
+impl_decomposable_for_integer!(u8);
 impl_decomposable_for_integer!(u16);
 impl_decomposable_for_integer!(u32);
 impl_decomposable_for_integer!(u64);
 impl_decomposable_for_integer!(u128);
 
+impl_decomposable_for_integer!(i8);
 impl_decomposable_for_integer!(i16);
 impl_decomposable_for_integer!(i32);
 impl_decomposable_for_integer!(i64);
@@ -43,3 +429,13 @@ impl_decomposable_for_integer!(isize);
 
 impl_decomposable_for_float!(f32);
 impl_decomposable_for_float!(f64);
+
+impl_decomposable_for_sign_ordered!(i8);
+impl_decomposable_for_sign_ordered!(i16);
+impl_decomposable_for_sign_ordered!(i32);
+impl_decomposable_for_sign_ordered!(i64);
+impl_decomposable_for_sign_ordered!(i128);
+impl_decomposable_for_sign_ordered!(isize);
+
+impl_decomposable_for_sign_ordered_float!(f32, u32);
+impl_decomposable_for_sign_ordered_float!(f64, u64);
@@ -1,7 +1,25 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
 mod radix_tree;
+mod children;
+mod trie_map;
+mod suffix_trie;
+mod terminated_trie;
 mod implementations;
+mod alphabets;
 
 pub use implementations::*;
+pub use alphabets::*;
+#[cfg(feature = "derive")]
+pub use triez_derive::Decomposable;
+
+/// paths used by the code `#[derive(Decomposable)]` expands to, not part of the api
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::boxed::Box;
+}
 
 /// A generic tree based collection storing decomposed items
 ///
@@ -14,32 +32,344 @@ pub use implementations::*;
 /// # Examples
 ///
 /// ```
-/// let mut trie = Trie::new(
-///     |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
-///     ('z' as usize) - ('a' as usize),
-/// );
-/// assert_eq!(trie.contains(&String::from("asd"))), false);
-/// trie.insert(String::from("asd")));
-/// assert_eq!(trie.contains(&String::from("asd"))), false);
+/// let mut trie = Trie::new(AsciiLower);
+/// assert_eq!(trie.contains("asd"), false);
+/// trie.insert(String::from("asd"));
+/// assert_eq!(trie.contains("asd"), true);
+/// ```
+pub type Trie<T, A> = radix_tree::Trie<T, A>;
+
+/// A `Trie` of strings
+///
+/// built with `StringTrie::ascii_lowercase` for case insensitive latin letters, or `StringTrie::bytes`
+/// for any string, stored as its utf-8 bytes.
+///
+/// # Examples
+///
+/// ```
+/// let mut trie = StringTrie::ascii_lowercase();
+/// trie.insert("Asd");
+/// assert!(trie.contains("asd"));
+/// ```
+pub type StringTrie<T, A> = Trie<T, A>;
+
+/// Builds a `StringTrie` holding the listed keys
+///
+/// the constructor of the alphabet comes first, `ascii_lowercase` or `bytes`, followed by the keys
+/// which are inserted in order.
+///
+/// # Examples
+///
+/// ```
+/// let trie = trie![ascii_lowercase; "asd", "dsa"];
+/// assert!(trie.contains("asd"));
+///
+/// let empty = trie![bytes;];
+/// assert!(empty.is_empty());
+/// ```
+#[macro_export]
+macro_rules! trie {
+    ($alphabet:ident; $($key:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut trie = $crate::StringTrie::$alphabet();
+        $(trie.insert($key);)*
+        trie
+    }};
+}
+
+/// The deserialized node tree of a `Trie`
+///
+/// `Trie` serializes its nodes but not its alphabet, so a trie is deserialized into this type and
+/// then rebuilt with `Trie::from_serialized`, handing back the alphabet it was built with.
+///
+/// # Examples
+///
+/// ```
+/// let mut trie = Trie::new(AsciiLower);
+/// trie.insert("asd");
+///
+/// let json = serde_json::to_string(&trie).unwrap();
+/// let data: SerializedTrie<char> = serde_json::from_str(&json).unwrap();
+/// let trie = Trie::from_serialized(AsciiLower, data).unwrap();
+/// assert!(trie.contains("asd"));
+/// ```
+#[cfg(feature = "serde")]
+pub type SerializedTrie<T> = radix_tree::SerializedTrie<T>;
+
+/// Iterator consuming a `Trie` into its elements
+///
+/// returned by `Trie::into_iter`, yielding the decomposed parts of every element in the same
+/// order as `Trie::iter`.
+pub type IntoIter<T> = radix_tree::IntoIter<T>;
+
+/// A node of a `Trie`, borrowed read-only
+///
+/// returned by `Trie::root_view`, for traversals over the nodes themselves which the built in
+/// queries and `Cursor` do not cover. views are tied to the borrow of the trie.
+///
+/// # Examples
+///
+/// ```
+/// let trie = trie![ascii_lowercase; "asd", "asx"];
+/// match trie.root_view() {
+///     NodeView::Run { parts, .. } => assert_eq!(parts, &['a', 's']),
+///     _ => unreachable!(),
+/// }
+/// ```
+pub type NodeView<'a, T> = radix_tree::NodeView<'a, T>;
+
+/// Iterator over the children of a `NodeView::Branch`
+pub type ChildViews<'a, T> = radix_tree::ChildViews<'a, T>;
+
+/// A position in a `Trie`, advanced one part at a time
+///
+/// returned by `Trie::cursor` at the root, for walks the built in queries do not cover. positions
+/// partway into a compressed run are cursors all the same.
+///
+/// # Examples
+///
+/// ```
+/// let trie = trie![ascii_lowercase; "asd", "asx"];
+/// let cursor = trie.cursor().child(&'a').unwrap().child(&'s').unwrap();
+/// assert_eq!(cursor.num_children(), 2);
+/// assert!(cursor.child(&'d').unwrap().is_terminal());
+/// ```
+pub type Cursor<'a, T, A> = radix_tree::Cursor<'a, T, A>;
+
+/// Matcher of a prefix typed one part at a time
+///
+/// returned by `Trie::prefix_matcher`, holding on to the position reached such that every keystroke
+/// only walks the part it adds, or removes.
+///
+/// # Examples
+///
+/// ```
+/// let trie = trie![ascii_lowercase; "as", "asd"];
+/// let mut matcher = trie.prefix_matcher();
+/// assert_eq!(matcher.push(&'a'), MatchState::Prefix);
+/// assert_eq!(matcher.push(&'s'), MatchState::Exact);
+/// assert_eq!(matcher.completions().count(), 2);
+/// ```
+pub type PrefixMatcher<'a, T, A> = radix_tree::PrefixMatcher<'a, T, A>;
+
+/// How the parts pushed into a `PrefixMatcher` relate to the elements of its trie
+pub type MatchState = radix_tree::MatchState;
+
+/// Counts of the nodes making up a `Trie`
+///
+/// returned by `Trie::stats`, for tuning and for reporting how a trie is laid out.
+///
+/// # Examples
+///
+/// ```
+/// let trie = trie![ascii_lowercase; "asd", "asx"];
+/// let stats = trie.stats();
+/// assert_eq!(stats.terminals, 2);
+/// println!("{:?}", stats);
+/// ```
+pub type TrieStats = radix_tree::TrieStats;
+
+/// A generic tree based map from decomposed keys to values
+///
+/// A `Trie` in which every stored key is associated with a value, the value living on the node
+/// where the decomposed key ends.
+///
+/// # Examples
+///
+/// ```
+/// let mut map = TrieMap::new(AsciiLower);
+/// assert_eq!(map.insert(String::from("asd"), 1), None);
+/// assert_eq!(map.insert(String::from("asd"), 2), Some(1));
+/// assert_eq!(map.get(String::from("asd")), Some(&2));
+/// ```
+pub type TrieMap<T, V, A> = trie_map::TrieMap<T, V, A>;
+
+/// The entry of a key in a `TrieMap`
+///
+/// returned by `TrieMap::entry`, either holding the value of the key or the key awaiting one.
+///
+/// # Examples
+///
+/// ```
+/// let mut counts = TrieMap::new(AsciiLower);
+/// for word in "the cat and the hat".split(' ') {
+///     *counts.entry(word).or_insert(0) += 1;
+/// }
+/// assert_eq!(counts.get("the"), Some(&2));
+/// ```
+pub type Entry<'a, T, V, A> = trie_map::Entry<'a, T, V, A>;
+
+/// The entry of a key holding a value in a `TrieMap`, see `Entry`
+pub type OccupiedEntry<'a, V> = trie_map::OccupiedEntry<'a, V>;
+
+/// The entry of a key without a value in a `TrieMap`, see `Entry`
+pub type VacantEntry<'a, T, V, A> = trie_map::VacantEntry<'a, T, V, A>;
+
+/// What `TrieMap::insert_with_policy` does with a key already holding a value
+///
+/// # Examples
+///
+/// ```
+/// let mut map = TrieMap::new(AsciiLower);
+/// map.insert("asd", 1);
+/// assert_eq!(map.insert_with_policy("asd", 2, InsertPolicy::Keep), Ok(Some(2)));
+/// assert_eq!(map.insert_with_policy("asd", 3, InsertPolicy::Error), Err(3));
+/// assert_eq!(map.get("asd"), Some(&1));
+/// ```
+pub type InsertPolicy = trie_map::InsertPolicy;
+
+/// A `Trie` queried by suffix
+///
+/// elements are stored back to front, such that finding the elements ending with a suffix is a
+/// prefix query. elements are handed in and yielded in their original order.
+///
+/// # Examples
+///
+/// ```
+/// let mut trie = SuffixTrie::new(AsciiLower);
+/// trie.insert("testing");
+/// trie.insert("running");
+/// assert_eq!(trie.count_suffix("ing"), 2);
+/// ```
+pub type SuffixTrie<T, A> = suffix_trie::SuffixTrie<T, A>;
+
+/// A `Trie` storing every element followed by a terminator part
+///
+/// the terminator takes a symbol of the alphabet, reserved by its index, such that every element
+/// ends in a leaf of its own. returned by `Trie::with_terminator`.
+///
+/// # Examples
+///
+/// ```
+/// let mut trie = Trie::with_terminator(|c: &char| if *c == '$' { 0 } else { *c as usize - 'a' as usize + 1 }, 27, 0);
+/// trie.insert("asd");
+/// assert!(trie.contains("asd"));
+/// assert!(!trie.contains("as"));
 /// ```
-pub type Trie<T, FIndex> = radix_tree::Trie<T, FIndex>;
+pub type TerminatedTrie<T, A> = terminated_trie::TerminatedTrie<T, A>;
+
+/// Trait that maps the parts of elements to the symbols of a trie's alphabet
+///
+/// `size` is the number of symbols, a count rather than the largest index, and `index` must map
+/// every part into `0..size`. this is checked as parts are inserted or queried. `index` need not
+/// be injective, parts mapped to the same index are the same symbol to the trie
+///
+/// for alphabets with `SKIPS` set, parts for which `skips` holds are dropped from elements as they
+/// are decomposed, on insertion and query alike, and are never indexed
+pub trait Alphabet<TParts> {
+    /// whether the alphabet skips any parts, `skips` is only consulted if so
+    const SKIPS: bool = false;
+
+    fn index(&self, part: &TParts) -> usize;
+
+    fn size(&self) -> usize;
+
+    /// whether `part` is left out of the elements it is a part of, none is by default
+    fn skips(&self, _part: &TParts) -> bool {
+        false
+    }
+}
 
 /// Trait that splits T into component parts
 ///
-/// this trait needs to be implemented in order for T to be placed into a trie
-pub trait Decomposable<TParts, TIterator: Iterator<Item=TParts>> {
-    fn decompose(self) -> TIterator;
+/// this trait needs to be implemented in order for T to be placed into a trie. the trie consumes
+/// the returned iterator lazily and only once, stopping as soon as the parts diverge from what is
+/// stored, so it need not be an `ExactSizeIterator`
+///
+/// the parts of a trie are fixed by its alphabet, every key inserted or queried must decompose into
+/// those same parts. a `String` decomposes into both `char` and `u8`, picked by the trie it is
+/// handed to
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not decompose into parts of `{TParts}`",
+    label = "this key does not decompose into the `{TParts}` parts of the trie",
+    note = "a trie only takes keys decomposing into the parts its alphabet indexes, convert the key or build the trie over an alphabet of its parts",
+)]
+pub trait Decomposable<TParts> {
+    type Iterator: Iterator<Item=TParts>;
+
+    fn decompose(self) -> Self::Iterator;
+}
+
+/// Trait that splits a borrowed T into component parts
+///
+/// allows querying a trie without moving or cloning the queried element
+pub trait DecomposableRef<'a, TParts> {
+    type Iterator: Iterator<Item=TParts>;
+
+    fn decompose_ref(&'a self) -> Self::Iterator;
+}
+
+/// Trait that lends the parts of a T already held by it
+///
+/// unlike `DecomposableRef` no part is created, the trie is handed references into T, such that
+/// querying with it does not allocate or copy any part
+pub trait DecomposeRef<'a, TParts: 'a> {
+    type Iterator: Iterator<Item=&'a TParts>;
+
+    fn parts(&'a self) -> Self::Iterator;
+}
+
+/// Errors surfaced by the fallible trie operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrieError {
+    /// the alphabet mapped a part to `got`, outside of `0..alphabet_size`
+    IndexOutOfRange { got: usize, alphabet_size: usize },
+    /// a serialized trie built over an alphabet of size `got` was given an alphabet of `expected`
+    AlphabetMismatch { expected: usize, got: usize },
+    /// a serialized trie whose node tree breaks an invariant of the trie, for the given reason
+    Malformed { reason: alloc::string::String },
 }
 
+impl core::fmt::Display for TrieError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            TrieError::IndexOutOfRange { got, alphabet_size } => {
+                write!(f, "index {} is out of range for alphabet_size {}", got, alphabet_size)
+            }
+            TrieError::AlphabetMismatch { expected, got } => {
+                write!(f, "expected an alphabet_size of {} but the trie was built with {}", expected, got)
+            }
+            TrieError::Malformed { reason } => write!(f, "malformed serialized trie: {}", reason),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TrieError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::Cell;
+
+    struct Probe<'a> {
+        chars: std::str::Chars<'a>,
+        pulled: &'a Cell<usize>,
+    }
+
+    impl<'a> Iterator for Probe<'a> {
+        type Item = char;
+
+        fn next(&mut self) -> Option<char> {
+            self.pulled.set(self.pulled.get() + 1);
+            self.chars.next()
+        }
+    }
+
+    impl<'a> Decomposable<char> for Probe<'a> {
+        type Iterator = Probe<'a>;
+
+        fn decompose(self) -> Probe<'a> {
+            self
+        }
+    }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_trie_simple() {
-        let mut trie = Trie::new(
+        let mut trie = Trie::from_fn(
             |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize), // index function
-            ('z' as usize) - ('a' as usize),                                      // alphabet size
+            ('z' as usize) - ('a' as usize) + 1,                                  // alphabet size
         );
 
         assert_eq!(trie.contains(String::from("asd")), false);
@@ -56,12 +386,2409 @@ mod tests {
     }
 
     #[test]
-    fn test_trie_simple_numeric() {
-        let mut trie = Trie::new(
-            |c: &u8| (*c as usize),
-            u8::max_value() as usize,
+    fn test_trie_retain() {
+        let index_fn = |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize);
+        let alphabet_size = ('z' as usize) - ('a' as usize) + 1;
+        let mut trie = Trie::from_iter_with(FnAlphabet::new(index_fn, alphabet_size), vec!["a", "ab", "abc", "abcd", "b", "bcd", "bcde", "cc"]);
+
+        trie.retain(|parts| parts.len() % 2 == 0);
+        let words = trie.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+        assert_eq!(words, vec!["ab", "abcd", "bcde", "cc"]);
+        assert_eq!(trie.len(), 4);
+        assert!(!trie.contains("abc"));
+        assert!(!trie.contains("bcd"));
+        // emptied nodes are collapsed as removal would
+        let expected = Trie::from_iter_with(FnAlphabet::new(index_fn, alphabet_size), vec!["ab", "abcd", "bcde", "cc"]);
+        assert_eq!(trie.tree().to_string(), expected.tree().to_string());
+
+        trie.retain(|_| false);
+        assert!(trie.is_empty());
+        assert!(trie.root.is_empty());
+    }
+
+    #[test]
+    fn test_trie_iter() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
         );
 
-        trie.insert(456 as u16);
+        assert_eq!(trie.iter().count(), 0);
+        trie.insert(String::from("asd"));
+        trie.insert(String::from("dsa"));
+        trie.insert(String::from("as"));
+
+        let elements = trie.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+        assert_eq!(elements, vec!["as", "asd", "dsa"]);
+    }
+
+    #[test]
+    fn test_trie_map() {
+        let mut map = TrieMap::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        assert_eq!(map.insert(String::from("asd"), 1), None);
+        assert_eq!(map.insert(String::from("as"), 2), None);
+        assert_eq!(map.insert(String::from("asd"), 3), Some(1));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(String::from("asd")), Some(&3));
+        assert_eq!(map.get(String::from("as")), Some(&2));
+        assert_eq!(map.get(String::from("a")), None);
+
+        *map.get_mut(String::from("as")).unwrap() += 10;
+        assert_eq!(map.get(String::from("as")), Some(&12));
+        assert_eq!(map.remove(String::from("as")), Some(12));
+        assert_eq!(map.remove(String::from("as")), None);
+        assert_eq!(map.get(String::from("asd")), Some(&3));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn test_trie_contains_ref() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        let key = String::from("asd");
+        assert!(!trie.contains_ref(&key));
+        trie.insert(String::from("asd"));
+        assert!(trie.contains_ref(&key));
+        assert!(!trie.contains_ref(&String::from("as")));
+        assert_eq!(key, "asd");
+    }
+
+    #[test]
+    fn test_trie_borrowed() {
+        let mut trie = Trie::from_fn(|c: &u8| *c as usize, 256);
+        let key = vec![1_u8, 2, 3];
+
+        assert!(!trie.contains_borrowed(&key));
+        assert!(trie.insert_borrowed(&key));
+        assert!(!trie.insert_borrowed(&key[..]));
+        assert!(trie.contains_borrowed(&key));
+        assert!(trie.contains_borrowed(&[1_u8, 2, 3]));
+        assert!(!trie.contains_borrowed(&key[..2]));
+        assert_eq!(key, vec![1, 2, 3]);
+        assert!(trie.contains(key));
+    }
+
+    #[test]
+    fn test_trie_str() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        let text = String::from("asd dsa");
+        trie.insert(&text[..3]);
+        trie.insert(String::from("dsa"));
+        assert!(trie.contains("asd"));
+        assert!(trie.contains(&text[4..]));
+        assert!(!trie.contains("as"));
+        assert!(trie.remove("asd"));
+        assert!(!trie.contains(String::from("asd")));
+    }
+
+    #[test]
+    fn test_trie_reconstruction() {
+        let index_fn = |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize);
+        let alphabet_size = ('z' as usize) - ('a' as usize) + 1;
+        let mut trie = Trie::from_fn(index_fn, alphabet_size);
+
+        // every branch keeps the part leading to it, on a split of a run as well as past one
+        trie.extend(vec!["abcd", "abxy", "abcz", "q"]);
+        let words = |trie: &Trie<char, _>| trie.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+        assert_eq!(words(&trie), vec!["abcd", "abcz", "abxy", "q"]);
+
+        // case folding maps 'A' and 'a' to the same symbol, the first inserted stands for both
+        let mut trie = Trie::from_fn(index_fn, alphabet_size);
+        trie.insert("ABcd");
+        trie.insert("abxy");
+        trie.insert("abCZ");
+        assert!(trie.contains("abcd"));
+        assert_eq!(trie.len(), 3);
+        assert_eq!(words(&trie), vec!["ABcd", "ABcZ", "ABxy"]);
+    }
+
+    #[test]
+    fn test_trie_sequences() {
+        let mut trie = Trie::from_fn(|t: &u32| *t as usize, 64);
+
+        trie.insert(vec![10_u32, 20, 30]);
+        trie.insert(&[10_u32, 20, 40][..]);
+        assert!(trie.contains(vec![10_u32, 20, 30]));
+        assert!(trie.contains(&[10_u32, 20, 40][..]));
+        assert!(!trie.contains(vec![10_u32, 20]));
+        assert_eq!(trie.iter().collect::<Vec<_>>(), vec![vec![10, 20, 30], vec![10, 20, 40]]);
+    }
+
+    #[test]
+    fn test_trie_lazy_decomposition() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.insert(String::from("asd"));
+        trie.insert(String::from("asx"));
+
+        let long = "q".repeat(1000);
+        let pulled = Cell::new(0);
+        assert!(!trie.contains(Probe { chars: long.chars(), pulled: &pulled }));
+        assert!(pulled.get() <= 2);
+
+        let pulled = Cell::new(0);
+        assert!(!trie.contains(Probe { chars: "asq".chars(), pulled: &pulled }));
+        assert_eq!(pulled.get(), 3);
+        assert!(trie.contains(String::from("asd")));
+    }
+
+    #[test]
+    fn test_trie_contains_all_any() {
+        let trie = Trie::from_iter_with(AsciiLower, vec!["car", "cart", "dog"]);
+        assert!(trie.contains_all(vec!["cart", "car", "dog"]));
+        assert!(!trie.contains_all(vec!["car", "ca", "dog"]));
+        assert!(trie.contains_any(vec!["ca", "cars", "dog"]));
+        assert!(!trie.contains_any(vec!["ca", "cars", "do"]));
+        assert!(trie.contains_all(Vec::<&str>::new()));
+        assert!(!trie.contains_any(Vec::<&str>::new()));
+
+        // stops at the first key deciding the answer
+        let pulled = Cell::new(0);
+        let probes = vec!["dog", "zebra", "car"].into_iter().map(|key| Probe { chars: key.chars(), pulled: &pulled });
+        assert!(!trie.contains_all(probes));
+        let after_zebra = pulled.get();
+        assert!(after_zebra <= 5);
+        let probes = vec!["car", "zebra"].into_iter().map(|key| Probe { chars: key.chars(), pulled: &pulled });
+        assert!(trie.contains_any(probes));
+        assert_eq!(pulled.get(), after_zebra + 4);
+    }
+
+    #[test]
+    fn test_trie_insert_lines() {
+        let mut trie = Trie::bytes();
+        let input = "car\ncart\r\n\ncar\r\n\ndog\n";
+        assert_eq!(trie.insert_lines(std::io::Cursor::new(input)).unwrap(), 3);
+        assert_eq!(trie.len(), 3);
+        assert!(trie.contains("cart"));
+        assert!(!trie.contains(""));
+        assert!(!trie.contains("cart\r"));
+        assert_eq!(trie.frequency("car"), 2);
+
+        // a last line without a newline is read all the same
+        assert_eq!(trie.insert_lines(std::io::Cursor::new("zebra")).unwrap(), 1);
+        assert!(trie.contains("zebra"));
+        assert!(trie.insert_lines(std::io::Cursor::new(vec![b'a', 0xff, b'\n'])).is_err());
+    }
+
+    #[test]
+    fn test_trie_index_out_of_range() {
+        let mut trie = Trie::from_fn(|c: &u8| *c as usize, 10);
+
+        assert_eq!(trie.try_insert(vec![1_u8, 2, 3]), Ok(true));
+        assert_eq!(trie.try_insert(vec![1_u8, 2, 3]), Ok(false));
+        assert_eq!(trie.try_insert(vec![1_u8, 12, 3]), Err(TrieError::IndexOutOfRange { got: 12, alphabet_size: 10 }));
+        assert_eq!(trie.len(), 1);
+        assert_eq!(trie.try_contains(vec![1_u8, 2, 3]), Ok(true));
+        assert_eq!(trie.try_contains(vec![1_u8, 2, 4]), Ok(false));
+        assert!(trie.try_contains(vec![10_u8]).is_err());
+    }
+
+    #[test]
+    fn test_trie_try_insert_batch() {
+        let mut trie = Trie::from_fn(|c: &u8| *c as usize, 10);
+        assert_eq!(trie.try_insert_batch(vec![vec![1_u8, 2], vec![1, 2, 3], vec![1, 2]]), Ok(2));
+        let before = trie.clone();
+        let stats = trie.stats();
+
+        let batch = vec![vec![4_u8, 5], vec![1, 2], vec![1, 12, 3], vec![6]];
+        assert_eq!(trie.try_insert_batch(batch), Err(TrieError::IndexOutOfRange { got: 12, alphabet_size: 10 }));
+        assert!(trie == before);
+        assert_eq!(trie.stats(), stats);
+        assert_eq!(trie.len(), 2);
+        assert_eq!(trie.frequency(vec![1_u8, 2]), 2);
+        assert!(!trie.contains(vec![4_u8, 5]));
+
+        assert_eq!(trie.try_insert_batch(vec![vec![4_u8, 5], vec![1, 2]]), Ok(1));
+        assert_eq!(trie.frequency(vec![1_u8, 2]), 3);
+        assert_eq!(trie.try_insert_batch(Vec::<Vec<u8>>::new()), Ok(0));
+    }
+
+    #[test]
+    fn test_trie_empty_alphabet() {
+        let mut trie = Trie::from_fn(|c: &u8| *c as usize, 0);
+        assert_eq!(trie.try_insert(vec![0_u8]), Err(TrieError::IndexOutOfRange { got: 0, alphabet_size: 0 }));
+        assert!(trie.is_empty());
+        assert!(trie.insert(Vec::<u8>::new()));
+        assert!(trie.contains(Vec::<u8>::new()));
+        assert_eq!(trie.try_contains(vec![0_u8]), Err(TrieError::IndexOutOfRange { got: 0, alphabet_size: 0 }));
+        assert_eq!(trie.iter().collect::<Vec<_>>(), vec![Vec::<u8>::new()]);
+
+        let mut fanout = Trie::with_root_fanout(FnAlphabet::new(|c: &u8| *c as usize, 0));
+        assert!(fanout.insert(Vec::<u8>::new()));
+        assert!(fanout.remove(Vec::<u8>::new()));
+        assert!(fanout.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "index 97 is out of range for alphabet_size 0")]
+    fn test_trie_empty_alphabet_panics() {
+        let mut trie = Trie::from_fn(|c: &char| *c as usize, 0);
+        trie.insert("a");
+    }
+
+    #[test]
+    #[should_panic(expected = "index 12 is out of range for alphabet_size 10")]
+    fn test_trie_index_out_of_range_panics() {
+        let mut trie = Trie::from_fn(|c: &u8| *c as usize, 10);
+        trie.insert(vec![1_u8, 12]);
+    }
+
+    #[test]
+    fn test_trie_full_alphabet() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.insert("abcdefghijklmnopqrstuvwxyz");
+        trie.insert("zza");
+        trie.insert("zzz");
+        assert!(trie.contains("abcdefghijklmnopqrstuvwxyz"));
+        assert!(trie.contains("zzz"));
+        assert!(!trie.contains("zz"));
+
+        let mut trie = Trie::from_fn(|c: &u8| *c as usize, u8::MAX as usize + 1);
+        trie.insert(u32::MAX);
+        trie.insert(0xFFFF_FF00_u32);
+        assert!(trie.contains(u32::MAX));
+    }
+
+    /// number of child slots allocated by `Normal` nodes below `node`
+    fn allocated_slots<T, V>(node: &radix_tree::Node<T, V>) -> usize {
+        match node {
+            radix_tree::Node::Empty | radix_tree::Node::Leaf(_) => 0,
+            radix_tree::Node::Normal { children, .. } => {
+                let own = match children {
+                    children::Children::Dense { slots, .. } => slots.len(),
+                    children::Children::Sparse(slots) => slots.len(),
+                };
+                own + children.iter().map(|(_, _, child)| allocated_slots(child)).sum::<usize>()
+            }
+            radix_tree::Node::Compressed { child, .. } => allocated_slots(child),
+        }
+    }
+
+    /// whether the occupied count of every dense `Normal` node below `node` matches its slots
+    fn occupied_counts_match<T, V>(node: &radix_tree::Node<T, V>) -> bool {
+        match node {
+            radix_tree::Node::Empty | radix_tree::Node::Leaf(_) => true,
+            radix_tree::Node::Normal { children, .. } => {
+                let own = match children {
+                    children::Children::Dense { slots, occupied } => slots.iter().filter(|slot| slot.is_some()).count() == *occupied,
+                    children::Children::Sparse(_) => true,
+                };
+                own && children.iter().all(|(_, _, child)| occupied_counts_match(child))
+            }
+            radix_tree::Node::Compressed { child, .. } => occupied_counts_match(child),
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Insert(String),
+        Remove(String),
+        Contains(String),
+    }
+
+    /// operations over short words of few letters, such that they often share prefixes and repeat
+    fn op() -> impl proptest::strategy::Strategy<Value=Op> {
+        use proptest::prelude::*;
+        let word = "[a-d]{0,6}";
+        prop_oneof![
+            2 => word.prop_map(Op::Insert),
+            1 => word.prop_map(Op::Remove),
+            1 => word.prop_map(Op::Contains),
+        ]
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_trie_matches_hash_set(ops in proptest::collection::vec(op(), 0..200)) {
+            let mut trie = Trie::new(AsciiLower);
+            let mut oracle = std::collections::HashSet::new();
+            for op in ops {
+                match op {
+                    Op::Insert(word) => proptest::prop_assert_eq!(trie.insert(word.as_str()), oracle.insert(word)),
+                    Op::Remove(word) => proptest::prop_assert_eq!(trie.remove(word.as_str()), oracle.remove(&word)),
+                    Op::Contains(word) => proptest::prop_assert_eq!(trie.contains(word.as_str()), oracle.contains(&word)),
+                }
+                for word in &oracle {
+                    proptest::prop_assert!(trie.contains(word.as_str()));
+                }
+                proptest::prop_assert_eq!(trie.len(), oracle.len());
+                proptest::prop_assert_eq!(trie.check_invariants(), Ok(()));
+            }
+            let mut words = oracle.into_iter().collect::<Vec<_>>();
+            words.sort();
+            proptest::prop_assert_eq!(trie.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>(), words);
+        }
+    }
+
+    #[test]
+    fn test_trie_fn_pointer_clone() {
+        fn index(c: &char) -> usize {
+            if *c == ' ' { 26 } else { (*c as usize) - ('a' as usize) }
+        }
+
+        type Index = fn(&char) -> usize;
+
+        #[derive(Clone)]
+        struct Dictionary {
+            words: Trie<char, FnAlphabet<Index>>,
+        }
+
+        let mut dictionary = Dictionary { words: Trie::from_fn(index as Index, 27) };
+        dictionary.words.insert("new york");
+        let mut cloned = dictionary.clone();
+        cloned.words.insert("york");
+        assert!(cloned.words.contains("new york"));
+        assert!(!dictionary.words.contains("york"));
+        assert_eq!((dictionary.words.len(), cloned.words.len()), (1, 2));
+
+        let alphabet = FnAlphabet::new(index as Index, 27);
+        let copied = alphabet;
+        let mut map = TrieMap::new(alphabet);
+        map.insert("a b", 1);
+        let mut cloned = map.clone();
+        cloned.insert("a b", 2);
+        assert_eq!((map.get("a b"), cloned.get("a b")), (Some(&1), Some(&2)));
+        assert_eq!(copied.size(), 27);
+    }
+
+    #[test]
+    fn test_trie_for_each_key() {
+        let mut trie = Trie::with_root_fanout(AsciiLower);
+        trie.extend(vec!["car", "cart", "carbon", "", "cat", "dog", "do", "zebra"]);
+        let mut visited = Vec::new();
+        trie.for_each_key(|parts| visited.push(parts.to_vec()));
+        assert_eq!(visited, trie.iter().collect::<Vec<_>>());
+        assert_eq!(visited.len(), 8);
+
+        let mut letters = 0;
+        Trie::new(AsciiLower).for_each_key(|parts| letters += parts.len());
+        trie.for_each_key(|parts| letters += parts.len());
+        assert_eq!(letters, 26);
+    }
+
+    #[test]
+    fn test_trie_chars() {
+        let mut trie = Trie::new(AsciiLower);
+        assert!(trie.insert('x'));
+        assert!(trie.insert('Y'));
+        assert!(!trie.insert('x'));
+        trie.insert("xy");
+        assert!(trie.contains('x'));
+        assert!(trie.contains('y'));
+        assert!(!trie.contains('z'));
+        assert_eq!(trie.len(), 3);
+        assert_eq!(trie.count_prefix('x'), 2);
+        assert!("xyz".chars().filter(|c| trie.contains(c)).eq(vec!['x', 'y']));
+    }
+
+    #[test]
+    fn test_trie_bits() {
+        let addr = |a: u8, b: u8, c: u8, d: u8| u32::from_be_bytes([a, b, c, d]);
+        let mut routes = Trie::binary();
+        routes.insert(Bits::prefix(addr(10, 0, 0, 0), 8));
+        routes.insert(Bits::prefix(addr(10, 1, 0, 0), 16));
+        routes.insert(Bits::prefix(addr(10, 1, 2, 128), 25));
+        routes.insert(Bits::prefix(addr(192, 168, 0, 0), 16));
+
+        let matched = |a, b, c, d| routes.longest_prefix_match(Bits::new(addr(a, b, c, d))).map(|bits| bits.len());
+        assert_eq!(matched(10, 1, 2, 3), Some(16));
+        assert_eq!(matched(10, 1, 2, 200), Some(25));
+        assert_eq!(matched(10, 2, 3, 4), Some(8));
+        assert_eq!(matched(192, 168, 7, 7), Some(16));
+        assert_eq!(matched(192, 169, 0, 0), None);
+        assert_eq!(matched(11, 0, 0, 0), None);
+
+        let found = routes.longest_prefix_match(Bits::new(addr(10, 1, 2, 3))).unwrap();
+        assert_eq!(found, Bits::prefix(addr(10, 1, 0, 0), 16).decompose().collect::<Vec<_>>());
+        assert_eq!(Bits::new(5_u8).decompose().collect::<Vec<_>>(), vec![false, false, false, false, false, true, false, true]);
+        assert_eq!(Bits::prefix(u64::MAX, 0).decompose().count(), 0);
+        assert!(routes.contains(Bits::prefix(addr(10, 1, 0, 0), 16)));
+        assert!(!routes.contains(Bits::prefix(addr(10, 1, 0, 0), 17)));
+    }
+
+    #[test]
+    fn test_trie_byte_slices() {
+        let mut trie = Trie::bytes();
+        trie.insert(b"GET");
+        trie.insert(b"GET /index");
+        trie.insert(&[0xff_u8, 0x00, 0x10][..]);
+        trie.insert(vec![0xff_u8]);
+
+        let buf = b"GET /index.html HTTP/1.1".to_vec();
+        assert!(trie.contains(&buf[..3]));
+        assert!(!trie.contains(&buf[..4]));
+        assert!(trie.contains(&buf[..10]));
+        assert!(!trie.contains(&buf[..]));
+        assert_eq!(trie.longest_prefix_match(&buf[..]), Some(b"GET /index".to_vec()));
+        assert_eq!(trie.count_prefix(b"GET"), 2);
+        assert!(trie.contains(&[0xff_u8][..]));
+        assert!(!trie.contains(&[0xff_u8, 0x00][..]));
+        assert_eq!(trie.keys_with_prefix(&[0xff_u8][..]).collect::<Vec<_>>(), vec![vec![0xff], vec![0xff, 0x00, 0x10]]);
+    }
+
+    #[test]
+    fn test_trie_contains_prefix_of() {
+        let mut trie = Trie::new(AsciiLower);
+        assert!(!trie.contains_prefix_of("abcd"));
+        trie.insert("ab");
+        assert!(trie.contains_prefix_of("abcd"));
+        assert!(trie.contains_prefix_of("ab"));
+        assert!(!trie.contains_prefix_of("a"));
+        assert!(!trie.contains_prefix_of("ac"));
+        assert!(!trie.contains_prefix_of(""));
+
+        let mut blocked = Trie::bytes();
+        blocked.extend(vec!["/admin/", "/private/keys/"]);
+        assert!(blocked.contains_prefix_of("/admin/users"));
+        assert!(blocked.contains_prefix_of("/private/keys/id_rsa"));
+        assert!(!blocked.contains_prefix_of("/private/notes"));
+        assert!(!blocked.contains_prefix_of("/adm"));
+        blocked.insert("");
+        assert!(blocked.contains_prefix_of("/public"));
+    }
+
+    #[test]
+    fn test_trie_part_counts() {
+        let mut trie = Trie::new(AsciiLower);
+        assert_eq!((trie.total_parts(), trie.stored_parts()), (0, 0));
+        trie.insert("asd");
+        assert_eq!((trie.total_parts(), trie.stored_parts()), (3, 3));
+        trie.insert("asx");
+        assert_eq!((trie.total_parts(), trie.stored_parts()), (6, 4));
+        trie.insert("as");
+        trie.insert("asd");
+        trie.insert("");
+        assert_eq!((trie.total_parts(), trie.stored_parts()), (8, 4));
+
+        let mut normal = Trie::with_min_compress_len(AsciiLower, 10);
+        normal.extend(vec!["asd", "asx", "as", ""]);
+        assert_eq!((normal.total_parts(), normal.stored_parts()), (8, 4));
+    }
+
+    #[test]
+    fn test_trie_check_invariants() {
+        let mut trie = Trie::new(AsciiLower);
+        assert_eq!(trie.check_invariants(), Ok(()));
+        trie.extend(vec!["car", "cart", "carbon", "cat", "dog", "", "zebra"]);
+        trie.remove("cart");
+        assert_eq!(trie.check_invariants(), Ok(()));
+
+        let run = |parts: &str, child| radix_tree::Node::Compressed { compressed: parts.chars().collect(), child: Box::new(child), value: None };
+        let mut corrupted = Trie::new(AsciiLower);
+        corrupted.insert("abcd");
+        corrupted.root = run("ab", run("cd", radix_tree::Node::Leaf(1)));
+        assert_eq!(corrupted.check_invariants(), Err(String::from("compressed run leads to a non terminal compressed run at [0, 1], rather than the two being one")));
+
+        corrupted.root = run("", radix_tree::Node::Leaf(1));
+        assert_eq!(corrupted.check_invariants(), Err(String::from("compressed run at [] is empty")));
+
+        corrupted.root = radix_tree::Node::Normal { children: children::Children::new_dense(3), value: Some(1) };
+        assert_eq!(corrupted.check_invariants(), Err(String::from("normal node at [] has 3 slots for an alphabet of 26")));
+
+        corrupted.root = radix_tree::Node::Compressed { compressed: vec!['a'], child: Box::new(radix_tree::Node::Leaf(1)), value: Some(1) };
+        assert_eq!(corrupted.check_invariants(), Err(String::from("2 terminal nodes for 1 elements")));
+    }
+
+    #[test]
+    fn test_children_occupied_count() {
+        let mut trie = Trie::with_root_fanout(AsciiLower);
+        let words = vec!["car", "cart", "carbon", "cat", "dog", "do", "zebra", "carbonate", "ca", "cab"];
+        trie.extend(words.clone());
+        assert!(occupied_counts_match(&trie.root));
+        for word in &["cart", "cat", "zebra", "missing"] {
+            trie.remove(*word);
+            assert!(occupied_counts_match(&trie.root));
+        }
+        trie.retain(|parts| parts.len() != 3);
+        assert!(occupied_counts_match(&trie.root));
+        trie.remove_prefix("carb");
+        trie.merge(Trie::from_iter_with(AsciiLower, words));
+        assert!(occupied_counts_match(&trie.root));
+        trie.shrink_to_fit();
+        assert!(occupied_counts_match(&trie.root));
+        assert!(occupied_counts_match(&trie.union(&Trie::from_iter_with(AsciiLower, vec!["cabs", "dot"])).root));
+    }
+
+    /// spare capacity of the runs below `node`
+    fn spare_run_capacity<T, V>(node: &radix_tree::Node<T, V>) -> usize {
+        match node {
+            radix_tree::Node::Empty | radix_tree::Node::Leaf(_) => 0,
+            radix_tree::Node::Normal { children, .. } => children.iter().map(|(_, _, child)| spare_run_capacity(child)).sum(),
+            radix_tree::Node::Compressed { compressed, child, .. } => compressed.capacity() - compressed.len() + spare_run_capacity(child),
+        }
+    }
+
+    #[test]
+    fn test_trie_shrink_to_fit() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.extend(vec!["asdf", "asx", "qwerty", "qwertz", "qwe"]);
+        if let radix_tree::Node::Normal { children, .. } = &mut trie.root {
+            if let Some((_, radix_tree::Node::Compressed { compressed, .. })) = children.get_mut(0) {
+                compressed.reserve(64);
+            }
+        }
+        let slots = allocated_slots(&trie.root);
+        assert!(spare_run_capacity(&trie.root) > 0);
+
+        trie.shrink_to_fit();
+        assert_eq!(spare_run_capacity(&trie.root), 0);
+        // every branch holds 2 of 26 positions
+        assert!(allocated_slots(&trie.root) < slots);
+        assert_eq!(allocated_slots(&trie.root), 6);
+        assert!(matches!(&trie.root, radix_tree::Node::Normal { children: children::Children::Sparse(_), .. }));
+
+        let words = trie.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+        assert_eq!(words, vec!["asdf", "asx", "qwe", "qwerty", "qwertz"]);
+        assert!(trie.insert("azz"));
+        assert!(trie.insert("b"));
+        assert!(trie.contains("qwertz"));
+        assert!(trie.remove("asx"));
+        assert!(trie.contains("asdf"));
+    }
+
+    #[test]
+    fn test_trie_sparse_wide_alphabet() {
+        let mut trie = Trie::from_fn(|c: &u8| *c as usize, 256);
+
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut keys = Vec::new();
+        for _ in 0..10_000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            keys.push(state);
+            trie.insert(state);
+        }
+
+        assert_eq!(trie.len(), 10_000);
+        assert!(keys.iter().all(|key| trie.contains(*key)));
+        assert!(allocated_slots(&trie.root) < 2 * keys.len());
+    }
+
+    #[cfg(all(unix, feature = "std"))]
+    #[test]
+    fn test_trie_os_str() {
+        use std::ffi::{OsStr, OsString};
+        use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+        let mut trie = Trie::from_fn(|c: &u8| *c as usize, 256);
+
+        trie.insert(OsString::from("/usr/bin"));
+        // not valid utf-8
+        trie.insert(OsStr::from_bytes(b"/usr/\xff\xfe"));
+        assert!(trie.contains(OsStr::new("/usr/bin")));
+        assert!(trie.contains(OsString::from_vec(b"/usr/\xff\xfe".to_vec())));
+        assert!(!trie.contains(OsStr::new("/usr")));
+        assert!(trie.starts_with(OsStr::new("/usr/")));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_trie_path_components() {
+        use std::path::PathBuf;
+
+        // the root directory and single letter directory names
+        let index_fn = |component: &OwnedComponent| match component {
+            OwnedComponent::Normal(name) => 1 + (name.to_str().unwrap().as_bytes()[0] - b'a') as usize,
+            _ => 0,
+        };
+        let mut trie = Trie::from_fn(index_fn, 27);
+
+        trie.insert(PathBuf::from("/a/b/c"));
+        trie.insert(PathBuf::from("/a/b/d"));
+        assert!(trie.contains(PathBuf::from("/a/b/c")));
+        assert!(trie.contains(PathBuf::from("/a/b/d/")));
+        assert!(!trie.contains(PathBuf::from("/a/b")));
+        assert_eq!(trie.count_prefix(PathBuf::from("/a/b")), 2);
+
+        // both paths share the run of "/", "a" and "b" before branching on their last component
+        match &trie.root {
+            radix_tree::Node::Compressed { compressed, child, .. } => {
+                assert_eq!(compressed.len(), 3);
+                assert!(matches!(**child, radix_tree::Node::Normal { .. }));
+            }
+            _ => panic!("expected the shared directories to be compressed"),
+        }
+    }
+
+    #[test]
+    fn test_trie_byte_arrays() {
+        let mut trie = Trie::from_fn(|c: &u8| *c as usize, 256);
+
+        trie.insert([0xde, 0xad, 0xbe, 0xef]);
+        trie.insert([0xde, 0xad, 0x00, 0x01]);
+        assert!(trie.contains([0xde, 0xad, 0xbe, 0xef]));
+        assert!(trie.contains([0xde, 0xad, 0x00, 0x01]));
+        assert!(!trie.contains([0xde, 0xad, 0xbe, 0xee]));
+        assert!(!trie.contains([0xde, 0xad]));
+        assert!(trie.contains(&[0xde, 0xad, 0xbe, 0xef][..]));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_trie_ip_routing() {
+        use std::net::{Ipv4Addr, Ipv6Addr};
+
+        // networks are stored as the octets of their prefix, addresses are looked up whole
+        let mut routes = Trie::from_fn(|c: &u8| *c as usize, 256);
+        routes.insert(&[10][..]);
+        routes.insert(&[10, 1][..]);
+        routes.insert(&[192, 168, 1][..]);
+        routes.insert(Ipv4Addr::new(192, 168, 1, 7));
+
+        assert_eq!(routes.longest_prefix_match(Ipv4Addr::new(10, 1, 2, 3)), Some(vec![10, 1]));
+        assert_eq!(routes.longest_prefix_match(Ipv4Addr::new(10, 2, 0, 1)), Some(vec![10]));
+        assert_eq!(routes.longest_prefix_match(Ipv4Addr::new(192, 168, 1, 20)), Some(vec![192, 168, 1]));
+        assert_eq!(routes.longest_prefix_match(Ipv4Addr::new(192, 168, 1, 7)), Some(vec![192, 168, 1, 7]));
+        assert_eq!(routes.longest_prefix_match(Ipv4Addr::new(172, 16, 0, 1)), None);
+        assert!(routes.contains(Ipv4Addr::new(192, 168, 1, 7)));
+
+        let mut routes = Trie::from_fn(|c: &u8| *c as usize, 256);
+        routes.insert(&[0x20, 0x01, 0x0d, 0xb8][..]);
+        let address = Ipv6Addr::new(0x2001, 0x0db8, 0, 0, 0, 0, 0, 1);
+        assert_eq!(routes.longest_prefix_match(address), Some(vec![0x20, 0x01, 0x0d, 0xb8]));
+        routes.insert(address);
+        assert_eq!(routes.longest_prefix_match(address), Some(address.octets().to_vec()));
+    }
+
+    #[test]
+    #[allow(unused_parens, clippy::legacy_numeric_constants, clippy::unnecessary_cast)]
+    fn test_trie_simple_numeric() {
+        let mut trie = Trie::from_fn(
+            |c: &u8| (*c as usize),
+            u8::max_value() as usize + 1,
+        );
+
+        trie.insert(456 as u16);
+        assert!(trie.contains(456_u16));
+        assert!(!trie.contains(457_u16));
+    }
+
+    #[test]
+    fn test_trie_numeric_widths() {
+        let mut trie = Trie::from_fn(|c: &u8| *c as usize, 256);
+
+        trie.insert(0x0102_0304_u32);
+        trie.insert(-2_i64);
+        trie.insert(1.5_f64);
+        assert!(trie.contains(0x0102_0304_u32));
+        assert!(trie.contains(-2_i64));
+        assert!(trie.contains(1.5_f64));
+        assert!(!trie.contains(0x0102_u16));
+        assert!(trie.iter().any(|bytes| bytes == vec![1, 2, 3, 4]));
+
+        let mut trie = Trie::new(Bytes);
+        trie.insert(0x7f_u8);
+        trie.insert(-1_i8);
+        assert!(trie.contains(0x7f_u8));
+        assert!(trie.contains(0xff_u8));
+        assert!(!trie.contains(0_u8));
+        assert!(trie.contains(vec![0x7f_u8]));
+        assert!(trie.starts_with(-1_i8));
+
+        // references decompose the same as the values they point to
+        let mut trie = Trie::new(Bytes);
+        let numbers = [42_u32, 7, 300];
+        let floats = [1.5_f64];
+        trie.extend(numbers.iter());
+        trie.extend(floats.iter());
+        let contains = |n: &u32| trie.contains(n);
+        assert!(contains(&42u32));
+        assert!(!contains(&43u32));
+        assert!(trie.contains(42u32));
+        assert!(floats.iter().all(|f| trie.contains(f)));
+        assert!(numbers.iter().all(|n| trie.contains(n)));
+    }
+
+    #[test]
+    fn test_trie_range() {
+        let mut trie = Trie::from_fn(|c: &u8| *c as usize, 256);
+        let numbers = [7_u32, 300, 0x0100_0000, 255, 256, 1, 0xffff_ffff, 299, 70_000];
+        trie.extend(numbers.iter().copied());
+        let as_u32 = |bytes: Vec<u8>| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+        // big endian bytes order the same as the numbers
+        let mut sorted = numbers.to_vec();
+        sorted.sort_unstable();
+        assert_eq!(trie.iter().map(as_u32).collect::<Vec<_>>(), sorted);
+
+        assert_eq!(trie.range(7_u32, 300_u32).map(as_u32).collect::<Vec<_>>(), vec![7, 255, 256, 299]);
+        assert_eq!(trie.range(8_u32, 301_u32).map(as_u32).collect::<Vec<_>>(), vec![255, 256, 299, 300]);
+        assert_eq!(trie.range(0_u32, 0xffff_ffff_u32).count(), numbers.len() - 1);
+        assert_eq!(trie.range(70_000_u32, 0x0100_0001_u32).map(as_u32).collect::<Vec<_>>(), vec![70_000, 0x0100_0000]);
+        assert_eq!(trie.range(300_u32, 300_u32).count(), 0);
+        assert_eq!(trie.range(301_u32, 300_u32).count(), 0);
+    }
+
+    #[test]
+    fn test_trie_sign_ordered() {
+        let mut trie = Trie::new(Bytes);
+        let numbers = [7_i32, -300, i32::MIN, 0, -1, i32::MAX, 256, -256, 1];
+        trie.extend(numbers.iter().copied().map(SignOrdered));
+        let as_i32 = |bytes: Vec<u8>| (u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) ^ 0x8000_0000) as i32;
+
+        let mut sorted = numbers.to_vec();
+        sorted.sort_unstable();
+        assert_eq!(trie.iter().map(as_i32).collect::<Vec<_>>(), sorted);
+        assert_eq!(trie.range(SignOrdered(-300), SignOrdered(7)).map(as_i32).collect::<Vec<_>>(), vec![-300, -256, -1, 0, 1]);
+        assert!(trie.contains(SignOrdered(-1)));
+        assert!(!trie.contains(-300));
+
+        // plain signed bytes put the negative numbers last
+        let plain = Trie::from_iter_with(Bytes, numbers.iter().copied());
+        let plain_order = plain.iter().map(|bytes| i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])).collect::<Vec<_>>();
+        assert_eq!(plain_order, vec![0, 1, 7, 256, i32::MAX, i32::MIN, -300, -256, -1]);
+    }
+
+    #[test]
+    fn test_trie_sign_ordered_floats() {
+        let mut trie = Trie::new(Bytes);
+        let numbers = [2.0_f64, f64::INFINITY, -1.5, 0.0, -0.0, f64::NEG_INFINITY, -f64::NAN, f64::NAN, 1e-300];
+        trie.extend(numbers.iter().copied().map(SignOrdered));
+        let as_f64 = |bytes: Vec<u8>| {
+            let bits = u64::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]]);
+            f64::from_bits(if bits & (1 << 63) != 0 { bits ^ (1 << 63) } else { !bits })
+        };
+
+        let mut sorted = numbers.to_vec();
+        sorted.sort_unstable_by(f64::total_cmp);
+        let stored = trie.iter().map(as_f64).collect::<Vec<_>>();
+        assert_eq!(stored.iter().map(|f| f.to_bits()).collect::<Vec<_>>(), sorted.iter().map(|f| f.to_bits()).collect::<Vec<_>>());
+        assert!(stored[0].is_nan() && stored[0].is_sign_negative());
+        assert!(stored[numbers.len() - 1].is_nan() && stored[numbers.len() - 1].is_sign_positive());
+
+        let range = trie.range(SignOrdered(-1.5), SignOrdered(f64::INFINITY)).map(as_f64).collect::<Vec<_>>();
+        assert_eq!(range, vec![-1.5, -0.0, 0.0, 1e-300, 2.0]);
+        assert!(trie.contains(SignOrdered(-0.0)));
+        assert_eq!(trie.len(), numbers.len());
+
+        let mut trie = Trie::new(Bytes);
+        trie.extend([-1.5_f32, 0.0, 2.0, f32::INFINITY].iter().copied().map(SignOrdered));
+        assert!(trie.iter().zip(trie.iter().skip(1)).all(|(a, b)| a < b));
+    }
+
+    #[test]
+    fn test_trie_range_prefixes() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.extend(vec!["a", "ab", "abc", "abd", "b", "ba", "c"]);
+        let range = |lo: &str, hi: &str| trie.range(lo, hi).map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+
+        // a prefix of lo is before it, hi itself is excluded
+        assert_eq!(range("ab", "b"), vec!["ab", "abc", "abd"]);
+        assert_eq!(range("abd", "ba"), vec!["abd", "b"]);
+        assert_eq!(range("aba", "abd"), vec!["abc"]);
+        assert_eq!(range("", "c"), vec!["a", "ab", "abc", "abd", "b", "ba"]);
+    }
+
+    #[test]
+    fn test_trie_set_operations() {
+        let index_fn = |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize);
+        let alphabet_size = ('z' as usize) - ('a' as usize) + 1;
+        let left = Trie::from_iter_with(FnAlphabet::new(index_fn, alphabet_size), vec!["car", "card", "cat", "dog", "do"]);
+        let right = Trie::from_iter_with(FnAlphabet::new(index_fn, alphabet_size), vec!["ca", "card", "cart", "dog", "zebra"]);
+        let words = |trie: &Trie<char, _>| trie.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+        let expected = |elements: Vec<&str>| Trie::from_iter_with(FnAlphabet::new(index_fn, alphabet_size), elements);
+
+        let union = left.union(&right);
+        assert_eq!(words(&union), vec!["ca", "car", "card", "cart", "cat", "do", "dog", "zebra"]);
+        assert_eq!(union.len(), 8);
+        let intersection = left.intersection(&right);
+        assert_eq!(words(&intersection), vec!["card", "dog"]);
+        assert_eq!(intersection.len(), 2);
+        let difference = left.difference(&right);
+        assert_eq!(words(&difference), vec!["car", "cat", "do"]);
+        assert_eq!(words(&right.difference(&left)), vec!["ca", "cart", "zebra"]);
+
+        // the results are shaped the same as tries built by inserting their elements
+        assert_eq!(union.tree().to_string(), expected(words(&union).iter().map(String::as_str).collect()).tree().to_string());
+        assert_eq!(intersection.tree().to_string(), expected(vec!["card", "dog"]).tree().to_string());
+        assert_eq!(difference.tree().to_string(), expected(vec!["car", "cat", "do"]).tree().to_string());
+        assert!(left.intersection(&Trie::from_fn(index_fn, alphabet_size)).is_empty());
+    }
+
+    #[test]
+    fn test_trie_map_insert_policy() {
+        let mut map = TrieMap::new(AsciiLower);
+        assert_eq!(map.insert_with_policy("key", 1, InsertPolicy::Error), Ok(None));
+        assert_eq!(map.insert_with_policy("keys", 2, InsertPolicy::Keep), Ok(None));
+        assert_eq!(map.insert_with_policy("ke", 3, InsertPolicy::Replace), Ok(None));
+        assert_eq!(map.len(), 3);
+
+        assert_eq!(map.insert_with_policy("key", 10, InsertPolicy::Keep), Ok(Some(10)));
+        assert_eq!(map.get("key"), Some(&1));
+        assert_eq!(map.insert_with_policy("key", 20, InsertPolicy::Error), Err(20));
+        assert_eq!(map.get("key"), Some(&1));
+        assert_eq!(map.insert_with_policy("key", 30, InsertPolicy::Replace), Ok(Some(1)));
+        assert_eq!(map.get("key"), Some(&30));
+        assert_eq!(map.insert_with_policy("key", 40, InsertPolicy::default()), Ok(Some(30)));
+        assert_eq!(map.len(), 3);
+    }
+
+    #[test]
+    fn test_trie_map_update() {
+        let mut counts = TrieMap::new(AsciiLower);
+        counts.insert("hits", 0);
+        assert!(counts.update("hits", |count| *count += 1));
+        assert!(counts.update("hits", |count| *count += 1));
+        assert!(!counts.update("hit", |count| *count += 1));
+        assert!(!counts.update("misses", |count| *count += 1));
+        assert_eq!(counts.get("hits"), Some(&2));
+        assert_eq!(counts.get("hit"), None);
+        assert_eq!(counts.len(), 1);
+    }
+
+    #[test]
+    fn test_trie_map_entry() {
+        let mut counts = TrieMap::new(AsciiLower);
+        for word in "the cat sat on the mat and the cats sat".split(' ') {
+            *counts.entry(word).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 7);
+        assert_eq!(counts.get("the"), Some(&3));
+        assert_eq!(counts.get("sat"), Some(&2));
+        assert_eq!(counts.get("cat"), Some(&1));
+        assert_eq!(counts.get("cats"), Some(&1));
+        assert_eq!(counts.get("ca"), None);
+
+        counts.entry("cat").and_modify(|count| *count += 10).or_insert(0);
+        counts.entry("dog").and_modify(|count| *count += 10).or_insert(5);
+        assert_eq!(counts.get("cat"), Some(&11));
+        assert_eq!(counts.get("dog"), Some(&5));
+        assert_eq!(*counts.entry("").or_default(), 0);
+        assert_eq!(counts.len(), 9);
+
+        match counts.entry("ma") {
+            Entry::Vacant(entry) => {
+                assert_eq!(entry.key(), &['m', 'a']);
+                *entry.insert(1) += 1;
+            }
+            Entry::Occupied(_) => panic!("expected a vacant entry"),
+        }
+        match counts.entry("MAT") {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.insert(7), 1);
+                assert_eq!(*entry.get(), 7);
+            }
+            Entry::Vacant(_) => panic!("expected an occupied entry"),
+        }
+        assert_eq!(counts.get("ma"), Some(&2));
+        assert_eq!(counts.get("mat"), Some(&7));
+        assert_eq!(counts.len(), 10);
+    }
+
+    #[test]
+    fn test_terminated_trie() {
+        let index_fn = |c: &char| if *c == '$' { 0 } else { (*c as usize) - ('a' as usize) + 1 };
+        let mut trie = Trie::with_terminator(index_fn, 27, 0);
+        assert!(trie.insert("asd"));
+        assert!(!trie.insert("asd"));
+        assert!(trie.contains("asd"));
+        assert!(!trie.contains("as"));
+        assert!(!trie.contains("asdf"));
+        assert!(!trie.contains(""));
+        assert_eq!(trie.count_prefix("as"), 1);
+
+        trie.extend(vec!["as", "", "b"]);
+        assert!(trie.contains("as"));
+        assert!(trie.contains(""));
+        assert_eq!(trie.len(), 4);
+        assert_eq!(trie.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>(), vec!["", "as", "asd", "b"]);
+        assert_eq!(trie.keys_with_prefix("as").count(), 2);
+        assert!(trie.remove("as"));
+        assert!(!trie.remove("as"));
+        assert!(trie.contains("asd"));
+        assert_eq!(trie.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 0 is reserved for the terminator")]
+    fn test_terminated_trie_reserved() {
+        let mut trie = TerminatedTrie::new(AsciiLower, 0);
+        trie.insert("ab");
+    }
+
+    #[test]
+    fn test_suffix_trie() {
+        let mut trie = SuffixTrie::new(AsciiLower);
+        trie.extend(vec!["testing", "running", "run", "ring", "sing"]);
+        let words = |it: &mut dyn Iterator<Item=Vec<char>>| it.map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+
+        assert_eq!(words(&mut trie.keys_with_suffix("ing")), vec!["running", "ring", "sing", "testing"]);
+        assert_eq!(words(&mut trie.keys_with_suffix("nning")), vec!["running"]);
+        assert_eq!(words(&mut trie.keys_with_suffix("un")), vec!["run"]);
+        assert!(trie.keys_with_suffix("ong").next().is_none());
+        assert_eq!(trie.count_suffix("ing"), 4);
+        assert_eq!(trie.count_suffix(""), 5);
+
+        assert!(trie.contains("testing"));
+        assert!(!trie.contains("ing"));
+        assert!(trie.remove("ring"));
+        assert_eq!(words(&mut trie.iter()), vec!["running", "sing", "testing", "run"]);
+        assert_eq!(trie.len(), 4);
+    }
+
+    #[test]
+    fn test_trie_cursor() {
+        let trie = Trie::from_iter_with(AsciiLower, vec!["asdf", "as", "asd", "b"]);
+        let root = trie.cursor();
+        assert!(!root.is_terminal());
+        assert_eq!(root.num_children(), 2);
+        assert!(root.child(&'c').is_none());
+
+        let a = root.child(&'a').unwrap();
+        assert!(!a.is_terminal());
+        assert_eq!(a.num_children(), 1);
+        let s = a.child(&'S').unwrap();
+        assert!(s.is_terminal());
+        assert_eq!(s.num_children(), 1);
+        let d = s.child(&'d').unwrap();
+        assert!(d.is_terminal());
+        assert!(d.child(&'x').is_none());
+        let f = d.child(&'f').unwrap();
+        assert!(f.is_terminal());
+        assert_eq!(f.num_children(), 0);
+        assert!(f.child(&'f').is_none());
+        assert_eq!(f.frequency(), 1);
+
+        // a run is followed part by part
+        let mut trie = Trie::new(AsciiLower);
+        trie.insert("asd");
+        trie.insert("asd");
+        let mut cursor = trie.cursor();
+        for c in "asd".chars() {
+            assert!(!cursor.is_terminal());
+            assert_eq!(cursor.num_children(), 1);
+            assert!(cursor.child(&'q').is_none());
+            cursor = cursor.child(&c).unwrap();
+        }
+        assert!(cursor.is_terminal());
+        assert_eq!(cursor.frequency(), 2);
+        assert_eq!(cursor.num_children(), 0);
+        assert_eq!(Trie::new(AsciiLower).cursor().num_children(), 0);
+    }
+
+    #[test]
+    fn test_trie_root_view() {
+        fn keys(view: NodeView<'_, char>, key: &mut String, found: &mut Vec<(String, usize)>) {
+            match view {
+                NodeView::Empty => {}
+                NodeView::Leaf { frequency } => found.push((key.clone(), frequency)),
+                NodeView::Branch { frequency, children } => {
+                    if frequency > 0 {
+                        found.push((key.clone(), frequency));
+                    }
+                    for (_, part, child) in children {
+                        key.push(*part);
+                        keys(child, key, found);
+                        key.pop();
+                    }
+                }
+                NodeView::Run { frequency, parts, child } => {
+                    if frequency > 0 {
+                        found.push((key.clone(), frequency));
+                    }
+                    key.extend(parts);
+                    keys(*child, key, found);
+                    key.truncate(key.len() - parts.len());
+                }
+            }
+        }
+
+        let mut trie = Trie::new(AsciiLower);
+        let mut found = Vec::new();
+        keys(trie.root_view(), &mut String::new(), &mut found);
+        assert!(found.is_empty());
+
+        trie.extend(vec!["car", "cart", "carbon", "", "cat", "dog", "do", "zebra", "car"]);
+        keys(trie.root_view(), &mut String::new(), &mut found);
+        let expected = trie.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+        assert_eq!(found.iter().map(|(key, _)| key.clone()).collect::<Vec<_>>(), expected);
+        assert_eq!(found.iter().find(|(key, _)| key == "car"), Some(&(String::from("car"), 2)));
+    }
+
+    #[test]
+    fn test_trie_prefix_matcher() {
+        let trie = Trie::from_iter_with(AsciiLower, vec!["as", "asdf", "asdg", "b"]);
+        let mut matcher = trie.prefix_matcher();
+        let completions = |matcher: &PrefixMatcher<char, AsciiLower>| matcher.completions()
+            .map(|parts| parts.into_iter().collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(matcher.state(), MatchState::Prefix);
+        assert_eq!(completions(&matcher).len(), 4);
+
+        assert_eq!(matcher.push(&'A'), MatchState::Prefix);
+        assert_eq!(matcher.push(&'s'), MatchState::Exact);
+        assert_eq!(completions(&matcher), vec!["as", "asdf", "asdg"]);
+        // "asd" ends inside the run leading to "asdf" and "asdg"
+        assert_eq!(matcher.push(&'d'), MatchState::Prefix);
+        assert_eq!(completions(&matcher), vec!["asdf", "asdg"]);
+        assert_eq!(matcher.push(&'x'), MatchState::NoMatch);
+        assert!(completions(&matcher).is_empty());
+        assert_eq!(matcher.push(&'f'), MatchState::NoMatch);
+        assert_eq!(matcher.len(), 5);
+
+        // backspace back to "asd"
+        assert_eq!(matcher.pop(), MatchState::NoMatch);
+        assert_eq!(matcher.pop(), MatchState::Prefix);
+        assert_eq!(matcher.push(&'g'), MatchState::Exact);
+        assert_eq!(completions(&matcher), vec!["asdg"]);
+        for _ in 0..3 {
+            matcher.pop();
+        }
+        assert_eq!(matcher.pop(), MatchState::Prefix);
+        assert!(matcher.is_empty());
+        assert_eq!(matcher.pop(), MatchState::Prefix);
+
+        matcher.push(&'q');
+        matcher.clear();
+        assert_eq!(matcher.push(&'b'), MatchState::Exact);
+        assert_eq!(Trie::new(AsciiLower).prefix_matcher().state(), MatchState::NoMatch);
+    }
+
+    #[test]
+    fn test_trie_run_capacities_match() {
+        fn capacities<T, V>(node: &radix_tree::Node<T, V>, found: &mut Vec<(usize, usize)>) {
+            match node {
+                radix_tree::Node::Empty | radix_tree::Node::Leaf(_) => {}
+                radix_tree::Node::Normal { children, .. } => children.iter().for_each(|(_, _, child)| capacities(child, found)),
+                radix_tree::Node::Compressed { compressed, child, .. } => {
+                    found.push((compressed.len(), compressed.capacity()));
+                    capacities(child, found);
+                }
+            }
+        }
+        let runs = |trie: &Trie<char, AsciiLower>| {
+            let mut found = Vec::new();
+            capacities(&trie.root, &mut found);
+            found
+        };
+
+        let words = vec!["interstellar", "internal", "internet", "in", "intern", "interstate", "inward", "x"];
+        let in_order = Trie::from_iter_with(AsciiLower, words.clone());
+        let reversed = Trie::from_iter_with(AsciiLower, words.iter().rev().copied());
+        let mut sorted = words.clone();
+        sorted.sort();
+        let from_sorted = Trie::from_sorted_iter(AsciiLower, sorted);
+        let mut merged = Trie::from_iter_with(AsciiLower, words[..3].to_vec());
+        merged.merge(Trie::from_iter_with(AsciiLower, words[3..].to_vec()));
+        let mut merged_back = Trie::from_iter_with(AsciiLower, words[3..].to_vec());
+        merged_back.merge(Trie::from_iter_with(AsciiLower, words[..3].to_vec()));
+        let mut removed = Trie::from_iter_with(AsciiLower, vec!["interstellarly", "inter", "interne", "intw"]);
+        removed.extend(words.clone());
+        for word in &["interstellarly", "inter", "interne", "intw"] {
+            removed.remove(*word);
+        }
+
+        assert!(runs(&in_order).iter().all(|(len, capacity)| len == capacity));
+        for trie in &[reversed, from_sorted, merged, merged_back, removed] {
+            assert!(*trie == in_order);
+            assert_eq!(runs(trie), runs(&in_order));
+            assert_eq!(trie.memory_usage(), in_order.memory_usage());
+        }
+    }
+
+    #[test]
+    fn test_trie_skipping_fn() {
+        let index_fn = |c: &char| match c {
+            'a'..='z' => Some((*c as usize) - ('a' as usize)),
+            '-' | ' ' => None,
+            _ => panic!("unexpected part {:?}", c),
+        };
+        let mut trie = Trie::from_skipping_fn(index_fn, 26);
+
+        assert!(trie.insert("e-mail"));
+        assert!(!trie.insert("email"));
+        assert!(trie.insert("to do"));
+        assert_eq!(trie.len(), 2);
+        assert!(trie.contains("email"));
+        assert!(trie.contains("e m-a-i l"));
+        assert!(trie.contains("todo"));
+        assert!(!trie.contains("e-"));
+        assert_eq!(trie.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>(), vec!["email", "todo"]);
+        assert_eq!(trie.count_prefix("e-m"), 1);
+        assert_eq!(trie.keys_with_prefix("t o").count(), 1);
+
+        let cursor = trie.cursor().child(&'t').unwrap();
+        assert!(cursor.child(&'-').unwrap().child(&'o').is_some());
+        let mut matcher = trie.prefix_matcher();
+        for c in "to d".chars() {
+            matcher.push(&c);
+        }
+        assert_eq!(matcher.len(), 3);
+        assert_eq!(matcher.push(&'o'), MatchState::Exact);
+
+        assert!(trie.remove("to-do"));
+        assert!(!trie.contains("todo"));
+
+        let mut map = TrieMap::new(SkipFnAlphabet::new(index_fn, 26));
+        map.insert("e-mail", 1);
+        assert_eq!(map.insert("email", 2), Some(1));
+        assert_eq!(map.get("e mail"), Some(&2));
+        assert_eq!(map.remove("e--mail"), Some(2));
+        assert!(map.is_empty());
+
+        // alphabets skipping nothing keep the size hint, which runs are allocated by
+        assert_eq!(radix_tree::kept(vec!['a'; 3].into_iter(), &AsciiLower).size_hint(), (3, Some(3)));
+        assert_eq!(radix_tree::kept(vec!['a'; 3].into_iter(), &SkipFnAlphabet::new(index_fn, 26)).size_hint(), (0, Some(3)));
+    }
+
+    #[test]
+    fn test_trie_min_compress_len() {
+        let words = vec!["car", "cart", "carbon", "cat", "dog", "do", "zebra", "carbonate", ""];
+        let compressed = Trie::from_iter_with(AsciiLower, words.clone());
+        let mut chained = Trie::with_min_compress_len(AsciiLower, 4);
+        chained.extend(words.clone());
+
+        assert_eq!(chained, compressed);
+        assert_eq!(chained.iter().collect::<Vec<_>>(), compressed.iter().collect::<Vec<_>>());
+        for word in &words {
+            assert!(chained.contains(*word));
+        }
+        assert!(!chained.contains("ca"));
+        assert!(!chained.contains("carbo"));
+        // "g" after "do" and "t" after "car" are single parts, kept out of runs
+        let stats = chained.stats();
+        assert!(stats.normal_nodes > compressed.stats().normal_nodes);
+        assert!(stats.compressed_nodes < compressed.stats().compressed_nodes);
+        assert_eq!(stats.terminals, words.len());
+
+        for word in &["cart", "do", "carbonate"] {
+            assert!(chained.remove(*word));
+        }
+        assert!(chained.insert("cartwheel"));
+        assert!(chained.contains("cartwheel"));
+        assert!(!chained.contains("cart"));
+        assert!(chained.contains("carbon"));
+        assert_eq!(chained.len(), words.len() - 2);
+
+        let mut always = Trie::with_min_compress_len(AsciiLower, 0);
+        always.extend(words);
+        assert_eq!(always.tree().to_string(), compressed.tree().to_string());
+    }
+
+    #[test]
+    fn test_trie_added_since() {
+        let words = |elements: Vec<Vec<char>>| elements.into_iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+        let older = Trie::from_iter_with(AsciiLower, vec!["car", "cart", "carbonate", "dog", "zebra"]);
+        let newer = Trie::from_iter_with(AsciiLower, vec!["car", "cart", "carbon", "carbonate", "ca", "dog", "dogs", "do", "yak"]);
+
+        assert_eq!(words(newer.added_since(&older)), vec!["ca", "carbon", "do", "dogs", "yak"]);
+        assert_eq!(words(older.added_since(&newer)), vec!["zebra"]);
+        assert!(newer.added_since(&newer.clone()).is_empty());
+        assert_eq!(words(newer.added_since(&Trie::new(AsciiLower))), words(newer.iter().collect()));
+        assert!(Trie::new(AsciiLower).added_since(&newer).is_empty());
+        assert_eq!(words(newer.added_since(&older)), words(newer.difference(&older).iter().collect()));
+    }
+
+    #[test]
+    fn test_trie_first_last() {
+        let numbers = [0x0102_0304_u32, 7, 0xffff_0000, 0x0102_0300, 0x8000_0000, 0x0102_0305];
+        let mut trie = Trie::bytes();
+        assert_eq!(trie.first(), None);
+        assert_eq!(trie.last(), None);
+        trie.extend(numbers.iter().copied());
+        let as_number = |parts: Vec<u8>| u32::from_be_bytes([parts[0], parts[1], parts[2], parts[3]]);
+        assert_eq!(trie.first().map(as_number), numbers.iter().copied().min());
+        assert_eq!(trie.last().map(as_number), numbers.iter().copied().max());
+
+        // an element comes before the elements it is a prefix of
+        let mut words = Trie::from_iter_with(AsciiLower, vec!["car", "cart", "carbon", "dog", "do"]);
+        let word = |parts: Option<Vec<char>>| parts.map(|parts| parts.into_iter().collect::<String>());
+        assert_eq!(word(words.first()), Some("car".to_string()));
+        assert_eq!(word(words.last()), Some("dog".to_string()));
+        words.insert("");
+        words.remove("dog");
+        assert_eq!(word(words.first()), Some(String::new()));
+        assert_eq!(word(words.last()), Some("do".to_string()));
+
+        let mut fanout = Trie::with_root_fanout(AsciiLower);
+        assert_eq!(fanout.last(), None);
+        fanout.insert("");
+        assert_eq!(word(fanout.first()), Some(String::new()));
+        assert_eq!(word(fanout.last()), Some(String::new()));
+    }
+
+    #[test]
+    fn test_trie_successor_predecessor() {
+        let trie = Trie::from_iter_with(AsciiLower, vec!["car", "cart", "carbon", "cat", "dog", "do", "zebra"]);
+        let word = |parts: Option<Vec<char>>| parts.map(|parts| parts.into_iter().collect::<String>());
+        let successor = |key: &str| word(trie.successor(key));
+        let predecessor = |key: &str| word(trie.predecessor(key));
+
+        // on members
+        assert_eq!(successor("car"), Some("carbon".to_string()));
+        assert_eq!(predecessor("carbon"), Some("car".to_string()));
+        assert_eq!(successor("cart"), Some("cat".to_string()));
+        assert_eq!(predecessor("cat"), Some("cart".to_string()));
+        assert_eq!(successor("do"), Some("dog".to_string()));
+        assert_eq!(predecessor("dog"), Some("do".to_string()));
+        // between members, inside runs and past them
+        assert_eq!(successor("carb"), Some("carbon".to_string()));
+        assert_eq!(predecessor("carb"), Some("car".to_string()));
+        assert_eq!(successor("carbonate"), Some("cart".to_string()));
+        assert_eq!(predecessor("carbonate"), Some("carbon".to_string()));
+        assert_eq!(successor("cb"), Some("do".to_string()));
+        assert_eq!(predecessor("cb"), Some("cat".to_string()));
+        assert_eq!(successor("dogs"), Some("zebra".to_string()));
+        assert_eq!(predecessor("e"), Some("dog".to_string()));
+        // outside the members
+        assert_eq!(successor(""), Some("car".to_string()));
+        assert_eq!(predecessor(""), None);
+        assert_eq!(predecessor("car"), None);
+        assert_eq!(predecessor("a"), None);
+        assert_eq!(successor("zebra"), None);
+        assert_eq!(successor("zz"), None);
+        assert_eq!(predecessor("zz"), Some("zebra".to_string()));
+        assert_eq!(Trie::new(AsciiLower).successor(""), None);
+    }
+
+    #[test]
+    fn test_trie_merge() {
+        let words = |trie: &Trie<char, AsciiLower>| trie.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+        let mut trie = Trie::from_iter_with(AsciiLower, vec!["car", "card", "cat", "dog", "do", "card"]);
+        trie.merge(Trie::from_iter_with(AsciiLower, vec!["ca", "card", "cart", "dog", "zebra", "carbonate"]));
+        assert_eq!(words(&trie), vec!["ca", "car", "carbonate", "card", "cart", "cat", "do", "dog", "zebra"]);
+        assert_eq!(trie.len(), 9);
+        assert_eq!(trie.frequency("card"), 3);
+        assert_eq!(trie.frequency("dog"), 2);
+        assert_eq!(trie.frequency("zebra"), 1);
+        // shaped the same as a trie built by inserting the elements
+        assert_eq!(trie.tree().to_string(), Trie::from_iter_with(AsciiLower, words(&trie)).tree().to_string());
+
+        // disjoint keys, merged into and out of an empty trie
+        let mut disjoint = Trie::new(AsciiLower);
+        disjoint.merge(Trie::from_iter_with(AsciiLower, vec!["abc", "abd"]));
+        disjoint.merge(Trie::new(AsciiLower));
+        disjoint.merge(Trie::from_iter_with(AsciiLower, vec!["xyz", ""]));
+        assert_eq!(words(&disjoint), vec!["", "abc", "abd", "xyz"]);
+        assert_eq!(disjoint.len(), 4);
+        assert_eq!(disjoint.tree().to_string(), Trie::from_iter_with(AsciiLower, words(&disjoint)).tree().to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "alphabet size")]
+    fn test_trie_merge_alphabet_mismatch() {
+        let index_fn = |c: &char| *c as usize;
+        let mut trie = Trie::from_fn(index_fn, 128);
+        trie.merge(Trie::from_fn(index_fn, 256));
+    }
+
+    #[test]
+    fn test_trie_len() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        assert!(trie.is_empty());
+        assert!(trie.insert(String::from("asd")));
+        assert!(!trie.insert(String::from("asd")));
+        assert_eq!(trie.len(), 1);
+        trie.insert(String::from("asx"));
+        trie.insert(String::from("dsa"));
+        trie.insert(String::from("dsa"));
+        assert_eq!(trie.len(), 3);
+        trie.remove(String::from("asd"));
+        trie.remove(String::from("asd"));
+        assert_eq!(trie.len(), 2);
+        assert!(!trie.is_empty());
+    }
+
+    #[test]
+    fn test_trie_insert_newly_added() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        // a new leaf, ending inside a run, ending at a run's start, past a branch and at a branch
+        assert!(trie.insert("asdf"));
+        assert!(trie.insert("as"));
+        assert!(!trie.insert("as"));
+        assert!(trie.insert("asx"));
+        assert!(trie.insert("asd"));
+        assert!(!trie.insert("asd"));
+        assert!(trie.insert(""));
+        assert!(!trie.insert(""));
+        assert!(!trie.insert("asdf"));
+        assert_eq!(trie.len(), 5);
+    }
+
+    #[test]
+    fn test_trie_empty_key() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        assert!(!trie.contains(String::new()));
+        assert!(!trie.remove(""));
+        trie.insert("asd");
+        assert!(!trie.contains(""));
+
+        // the empty element ends at the root, marking it terminal
+        assert!(trie.insert(String::new()));
+        assert!(trie.contains(""));
+        assert!(!trie.contains("as"));
+        assert_eq!(trie.len(), 2);
+        assert_eq!(trie.iter().collect::<Vec<_>>(), vec![vec![], vec!['a', 's', 'd']]);
+
+        assert!(trie.remove(""));
+        assert!(!trie.contains(""));
+        assert!(trie.contains("asd"));
+        assert_eq!(trie.len(), 1);
+
+        // alone in the trie it is held by a root leaf
+        trie.remove("asd");
+        trie.insert("");
+        assert!(matches!(trie.root, radix_tree::Node::Leaf(_)));
+        assert!(trie.remove(""));
+        assert!(trie.root.is_empty());
+
+        let mut map = TrieMap::from_fn(|c: &char| (*c as usize) - ('a' as usize), 26);
+        assert_eq!(map.get(""), None);
+        assert_eq!(map.insert("", 1), None);
+        assert_eq!(map.insert("a", 2), None);
+        assert_eq!(map.get(""), Some(&1));
+        assert_eq!(map.remove(""), Some(1));
+        assert_eq!(map.get(""), None);
+        assert_eq!(map.get("a"), Some(&2));
+    }
+
+    #[test]
+    fn test_trie_drain() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.extend(vec!["dsa", "asd", "as", "asdf"]);
+        let drained = trie.drain().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+        assert_eq!(drained, vec!["as", "asd", "asdf", "dsa"]);
+        assert!(trie.is_empty());
+        assert!(!trie.contains("asd"));
+        assert_eq!(trie.iter().count(), 0);
+
+        trie.insert("asd");
+        assert_eq!(trie.len(), 1);
+    }
+
+    #[test]
+    fn test_trie_with_root_fanout() {
+        let mut fanout = Trie::with_root_fanout(Bytes);
+        let mut lazy = Trie::from_fn(|c: &u8| *c as usize, 256);
+        assert!(!fanout.starts_with(&[][..]));
+        assert!(!fanout.contains(&[][..]));
+        match &fanout.root {
+            radix_tree::Node::Normal { children: children::Children::Dense { slots, .. }, value: None } => assert_eq!(slots.len(), 256),
+            _ => panic!("expected a dense root"),
+        }
+
+        for key in &[0x0102_u16, 0xff00, 0x0103, 0x7f7f, 0] {
+            assert_eq!(fanout.insert(*key), lazy.insert(*key));
+        }
+        assert_eq!(fanout.len(), lazy.len());
+        assert_eq!(fanout.iter().collect::<Vec<_>>(), lazy.iter().collect::<Vec<_>>());
+        assert!(fanout.contains(0xff00_u16));
+        assert!(!fanout.contains(0xff01_u16));
+        assert!(fanout.starts_with(&[0x01][..]));
+        assert!(fanout.insert(&[][..]));
+        assert!(fanout.contains(&[][..]));
+
+        for key in &[0x0102_u16, 0xff00, 0x0103, 0x7f7f, 0] {
+            assert!(fanout.remove(*key));
+        }
+        assert!(fanout.remove(&[][..]));
+        assert!(fanout.is_empty());
+        assert!(!fanout.starts_with(&[][..]));
+    }
+
+    #[test]
+    fn test_trie_eq() {
+        let index_fn = |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize);
+        let alphabet_size = ('z' as usize) - ('a' as usize) + 1;
+
+        let forward = Trie::from_iter_with(FnAlphabet::new(index_fn, alphabet_size), vec!["ab", "ac"]);
+        let backward = Trie::from_iter_with(FnAlphabet::new(index_fn, alphabet_size), vec!["ac", "ab"]);
+        assert_eq!(forward, backward);
+
+        // differently shaped nodes holding the same elements
+        let mut removed = Trie::from_iter_with(FnAlphabet::new(index_fn, alphabet_size), vec!["asdf", "asx", "b", "qwe"]);
+        removed.remove("asx");
+        removed.remove("b");
+        let mut fanout = Trie::with_root_fanout(FnAlphabet::new(index_fn, alphabet_size));
+        fanout.extend(vec!["qwe", "ASDF", "asdf"]);
+        fanout.shrink_to_fit();
+        assert_eq!(removed, fanout);
+        assert_eq!(Trie::from_fn(index_fn, alphabet_size), Trie::with_root_fanout(FnAlphabet::new(index_fn, alphabet_size)));
+
+        assert_ne!(forward, Trie::from_iter_with(FnAlphabet::new(index_fn, alphabet_size), vec!["ab"]));
+        assert_ne!(forward, Trie::from_iter_with(FnAlphabet::new(index_fn, alphabet_size), vec!["ab", "ad"]));
+        assert_ne!(forward, Trie::from_iter_with(FnAlphabet::new(index_fn, alphabet_size), vec!["ab", "a"]));
+        assert_ne!(forward, Trie::from_iter_with(FnAlphabet::new(index_fn, alphabet_size), vec!["ab", "acx"]));
+        assert_eq!(format!("{:?}", forward), "{['a', 'b'], ['a', 'c']}");
+    }
+
+    #[test]
+    fn test_trie_into_iter() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.extend(vec!["dsa", "asd", "", "as", "asdf", "asx", "qwe"]);
+        let borrowed = trie.iter().collect::<Vec<_>>();
+        let mut consumed = Vec::new();
+        for parts in trie {
+            consumed.push(parts);
+        }
+        assert_eq!(consumed, borrowed);
+        assert_eq!(consumed.len(), 7);
+        assert_eq!(consumed[0], Vec::<char>::new());
+        assert_eq!(consumed[3], vec!['a', 's', 'd', 'f']);
+    }
+
+    #[test]
+    fn test_trie_from_iter_with() {
+        let mut trie = Trie::from_iter_with(AsciiLower, vec!["asd", "dsa"]);
+
+        assert!(trie.contains("asd"));
+        assert!(trie.contains("dsa"));
+        assert_eq!(trie.len(), 2);
+
+        trie.extend(vec!["as", "asd"]);
+        assert!(trie.contains("as"));
+        assert_eq!(trie.len(), 3);
+    }
+
+    #[test]
+    fn test_trie_stats() {
+        let mut trie = Trie::new(AsciiLower);
+        assert_eq!(trie.stats(), TrieStats { empty_nodes: 1, ..TrieStats::default() });
+
+        // root -a-> [s] -> { -d-> leaf, -x-> leaf } plus root -b-> leaf, "as" ending at the inner branch
+        trie.extend(vec!["asd", "asx", "b", "as"]);
+        assert_eq!(trie.stats(), TrieStats {
+            empty_nodes: 0,
+            leaf_nodes: 3,
+            normal_nodes: 2,
+            compressed_nodes: 1,
+            compressed_parts: 1,
+            max_depth: 3,
+            terminals: 4,
+        });
+        assert_eq!(trie.stats().terminals, trie.len());
+
+        trie.remove("b");
+        trie.remove("asx");
+        let stats = trie.stats();
+        assert_eq!((stats.normal_nodes, stats.compressed_nodes, stats.leaf_nodes), (0, 2, 1));
+        assert_eq!(stats.compressed_parts, 3);
+        assert!(format!("{:?}", stats).contains("compressed_parts: 3"));
+    }
+
+    #[test]
+    fn test_trie_memory_usage() {
+        let mut trie = StringTrie::bytes();
+        let empty = trie.memory_usage();
+        assert_eq!(empty, std::mem::size_of_val(&trie));
+
+        let mut last = empty;
+        for i in 0..200 {
+            trie.insert(format!("a rather long key shared by many elements #{:03} and its tail", i * 7 % 200));
+            let usage = trie.memory_usage();
+            assert!(usage >= last, "memory usage went from {} to {} bytes", last, usage);
+            last = usage;
+        }
+        assert!(last > empty + 200 * "and its tail".len());
+
+        // branches of two out of 26 letters are a lot cheaper sparse than dense
+        let mut trie = Trie::new(AsciiLower);
+        trie.extend(vec!["aa", "ab", "ba", "bb"]);
+        let dense = trie.memory_usage();
+        trie.shrink_to_fit();
+        assert!(trie.memory_usage() < dense);
+
+        trie.clear();
+        assert_eq!(trie.memory_usage(), std::mem::size_of_val(&trie));
+    }
+
+    #[test]
+    fn test_trie_from_sorted_iter() {
+        let sets: Vec<Vec<&str>> = vec![
+            vec![],
+            vec![""],
+            vec!["asd"],
+            vec!["", "a", "ab", "abc", "abd", "b"],
+            vec!["car", "card", "care", "careful", "cart", "cat", "dog", "dogs", "door"],
+            vec!["in", "in", "inn", "inner", "inner", "inner", "int", "into", "io"],
+        ];
+        for words in sets {
+            let sorted = Trie::from_sorted_iter(AsciiLower, words.clone());
+            let inserted = Trie::from_iter_with(AsciiLower, words.clone());
+            assert_eq!(sorted.tree().to_string(), inserted.tree().to_string());
+            assert_eq!(sorted.len(), inserted.len());
+            assert_eq!(sorted, inserted);
+            for word in words {
+                assert_eq!(sorted.frequency(word), inserted.frequency(word));
+            }
+        }
+
+        // order is by index, so case does not matter and the first inserted part is kept
+        let mut mixed = Trie::from_sorted_iter(AsciiLower, vec!["Ab", "aC"]);
+        assert_eq!(mixed.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>(), vec!["Ab", "AC"]);
+        mixed.insert("abc");
+        assert!(mixed.contains("ABC"));
+        assert_eq!(mixed.len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "from_sorted_iter was given unsorted elements")]
+    fn test_trie_from_sorted_iter_unsorted() {
+        Trie::from_sorted_iter(AsciiLower, vec!["b", "a", "c", "ab"]);
+    }
+
+    #[test]
+    fn test_string_trie() {
+        let mut lower = StringTrie::ascii_lowercase();
+        lower.extend(vec!["Hello", "WORLD", "help"]);
+        assert!(lower.contains("hello"));
+        assert!(lower.contains("World"));
+        assert!(lower.contains("HELP"));
+        assert_eq!(lower.len(), 3);
+        assert!(lower.try_insert("héllo").is_err());
+        assert!(lower.try_insert("a b").is_err());
+
+        let mut bytes = StringTrie::bytes();
+        bytes.extend(vec!["Hello", "hello", "grüße", "日本語", ""]);
+        assert!(bytes.contains("Hello"));
+        assert!(bytes.contains("hello"));
+        assert!(bytes.contains(String::from("grüße")));
+        assert!(bytes.contains("日本語"));
+        assert!(bytes.contains(""));
+        assert!(!bytes.contains("HELLO"));
+        // prefixes are over bytes, so one cut within a character still prefixes the stored string
+        assert!(bytes.starts_with("日本"));
+        assert!(bytes.starts_with(&"日本語".as_bytes()[..1]));
+        assert!(!bytes.contains(&"日本語".as_bytes()[..1]));
+        assert_eq!(bytes.len(), 5);
+    }
+
+    #[test]
+    fn test_trie_macro() {
+        let trie = trie![ascii_lowercase; "asd", "dsa", String::from("Asx"),];
+        assert_eq!(trie.len(), 3);
+        assert!(trie.contains("asd"));
+        assert!(trie.contains("dsa"));
+        assert!(trie.contains("asx"));
+        assert_eq!(trie, Trie::from_iter_with(AsciiLower, vec!["asd", "dsa", "asx"]));
+
+        let repeated = trie![bytes; "a", "a"];
+        assert_eq!(repeated.len(), 1);
+        assert_eq!(repeated.frequency("a"), 2);
+
+        let empty = trie![bytes;];
+        assert!(empty.is_empty());
+        assert!(!empty.contains(""));
+    }
+
+    #[test]
+    fn test_trie_get_key() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+        let key = |trie: &Trie<char, _>, query| trie.get_key(query).map(|parts| parts.into_iter().collect::<String>());
+        trie.insert("asd");
+        trie.insert("ASDF");
+        trie.insert("Asdfgh");
+
+        assert_eq!(key(&trie, "ASD"), Some("asd".to_string()));
+        assert_eq!(key(&trie, "asdf"), Some("asdF".to_string()));
+        assert_eq!(key(&trie, "aSdFgH"), Some("asdFgh".to_string()));
+        // prefixes which are not members, ending at a branch and inside a run
+        assert_eq!(key(&trie, "as"), None);
+        assert_eq!(key(&trie, "asdfg"), None);
+        assert_eq!(key(&trie, "asdx"), None);
+        assert_eq!(key(&trie, "asdfghj"), None);
+    }
+
+    #[test]
+    fn test_trie_case_insensitive() {
+        let mut trie = Trie::new(Utf8);
+        trie.insert(CaseInsensitive("ASD"));
+        trie.insert(CaseInsensitive("AsX"));
+        trie.insert(CaseInsensitive(String::from("asd")));
+        assert!(trie.contains("asd"));
+        assert!(!trie.contains("ASD"));
+        assert!(trie.contains(CaseInsensitive("aSd")));
+        assert_eq!(trie.len(), 2);
+        assert_eq!(trie.frequency("asd"), 2);
+
+        // the shared prefix is stored lowercase whichever case reached it first
+        let words = trie.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+        assert_eq!(words, vec!["asd", "asx"]);
+
+        // 'İ' lowercases into two chars, both are stored
+        trie.insert(CaseInsensitive("İ"));
+        assert!(trie.contains("i\u{307}"));
+        assert!(!trie.contains("i"));
+        assert!(trie.contains(CaseInsensitive("İ")));
+    }
+
+    #[test]
+    fn test_trie_alphabets() {
+        #[derive(Clone, Debug)]
+        struct Digits;
+
+        impl Alphabet<char> for Digits {
+            fn index(&self, part: &char) -> usize {
+                part.to_digit(10).map_or(10, |digit| digit as usize)
+            }
+
+            fn size(&self) -> usize {
+                10
+            }
+        }
+
+        let mut lower = Trie::new(AsciiLower);
+        lower.insert("Asd");
+        assert!(lower.contains("asd"));
+        assert!(lower.contains("ASD"));
+        assert_eq!(lower.try_insert("as-d"), Err(TrieError::IndexOutOfRange { got: ('-' as usize).wrapping_sub('a' as usize), alphabet_size: 26 }));
+
+        let mut bytes = Trie::new(Bytes);
+        bytes.insert(vec![0_u8, 255, 7]);
+        assert!(bytes.contains(vec![0_u8, 255, 7]));
+        assert!(!bytes.contains(vec![0_u8, 255]));
+
+        let mut utf8 = Trie::new(Utf8);
+        utf8.extend(vec!["grüße", "grün", "日本"]);
+        assert!(utf8.contains("grün"));
+        assert!(utf8.contains("日本"));
+        assert!(!utf8.contains("gru"));
+
+        let mut digits = Trie::new(Digits);
+        digits.insert("0123");
+        assert!(digits.contains("0123"));
+        assert_eq!(digits.try_insert("12a"), Err(TrieError::IndexOutOfRange { got: 10, alphabet_size: 10 }));
+
+        let mut from_fn = Trie::from_fn(|c: &u32| *c as usize, 64);
+        from_fn.insert(vec![1_u32, 63]);
+        assert!(from_fn.contains(vec![1_u32, 63]));
+        assert_eq!(format!("{:?}", from_fn.clone()), format!("{:?}", from_fn));
+    }
+
+    #[test]
+    fn test_trie_extend() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.extend(vec!["a".to_string(), "b".to_string()]);
+        trie.extend("asd dsa".split(' ').filter(|word| word.len() > 1));
+        assert!(trie.contains("a"));
+        assert!(trie.contains("b"));
+        assert!(trie.contains("asd"));
+        assert!(trie.contains("dsa"));
+        assert_eq!(trie.len(), 4);
+
+        let mut map = TrieMap::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+        map.extend(vec![("asd", 1), ("as", 2), ("asd", 3)]);
+        assert_eq!(map.get("asd"), Some(&3));
+        assert_eq!(map.get("as"), Some(&2));
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn test_trie_clone() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.extend(vec!["asd", "asx", "dsa"]);
+        let snapshot = trie.clone();
+        trie.remove("asd");
+        trie.insert("qwe");
+
+        assert!(snapshot.contains("asd"));
+        assert!(snapshot.contains("asx"));
+        assert!(!snapshot.contains("qwe"));
+        assert_eq!(snapshot.len(), 3);
+        assert!(!trie.contains("asd"));
+        assert!(trie.contains("qwe"));
+    }
+
+    #[test]
+    fn test_trie_tree_display() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        assert_eq!(trie.tree().to_string(), "Empty\n");
+        trie.extend(vec!["asd", "asx", "as"]);
+        let rendered = trie.tree().to_string();
+        assert_eq!(rendered, concat!(
+            "Compressed { compressed: ['a', 's'], terminal: false }\n",
+            "  Normal { children: [3, 23], terminal: true }\n",
+            "    3 'd' ->\n",
+            "      Leaf\n",
+            "    23 'x' ->\n",
+            "      Leaf\n",
+        ));
+    }
+
+    #[test]
+    fn test_trie_to_dot() {
+        let mut trie = Trie::from_fn(|c: &char| (*c as usize) - ('"' as usize), 128);
+        assert_eq!(trie.to_dot(), "digraph trie {\n    n0 [label=\"Empty\", shape=circle];\n}\n");
+
+        trie.extend(vec!["asd", "asx", "as", "q\""]);
+        let dot = trie.to_dot();
+        assert!(dot.starts_with("digraph trie {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(dot.matches('{').count(), dot.matches('}').count());
+        assert_eq!(dot.matches(" -> ").count(), 6);
+        assert!(dot.contains("n0 [label=\"Normal\", shape=circle];"));
+        assert!(dot.contains("[label=\"['s']\"];"));
+        assert!(dot.contains(&format!("[label=\"{}\"];", 'x' as usize - '"' as usize)));
+        assert!(dot.contains("[label=\"Normal\", shape=doublecircle];"));
+        assert_eq!(dot.matches("[label=\"Leaf\", shape=doublecircle];").count(), 3);
+        // quotes in parts are escaped
+        assert!(dot.contains(r#"[label="['\"']"];"#));
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn test_trie_graphemes() {
+        let precomposed = "caf\u{e9}";
+        let decomposed = "cafe\u{301}";
+        let clusters = ["c", "a", "f", "e", "\u{e9}", "e\u{301}", "s"];
+        let index_fn = |part: &String| clusters.iter().position(|cluster| cluster == part).unwrap();
+        let mut trie = Trie::from_fn(index_fn, clusters.len());
+        trie.insert(GraphemeString(decomposed));
+        trie.insert(GraphemeString("cafes"));
+
+        // the combining accent is a part of its cluster rather than a level of its own
+        assert_eq!(Decomposable::<String>::decompose(GraphemeString(decomposed)).count(), 4);
+        assert_eq!(decomposed.chars().count(), 5);
+        assert_eq!(trie.count_prefix(GraphemeString("cafe")), 1);
+        let found = trie.keys_with_prefix(GraphemeString(decomposed)).collect::<Vec<_>>();
+        assert_eq!(found, vec![vec!["c", "a", "f", "e\u{301}"]]);
+        let mut chars = Trie::new(Utf8);
+        chars.extend(vec![decomposed, "cafes"]);
+        assert_eq!(chars.count_prefix("cafe"), 2);
+
+        // spellings reading the same are still different clusters
+        assert!(trie.contains(GraphemeString(decomposed)));
+        assert!(!trie.contains(GraphemeString(precomposed)));
+        let index_fn = |part: &&str| clusters.iter().position(|cluster| cluster == part).unwrap();
+        let mut borrowed = Trie::from_fn(index_fn, clusters.len());
+        borrowed.insert(GraphemeString(precomposed));
+        assert!(borrowed.contains(GraphemeString(precomposed)));
+        assert!(!borrowed.contains(GraphemeString(decomposed)));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_trie_par_extend() {
+        let words = vec!["car", "Cart", "carbon", "cat", "dog", "do", "zebra", "", "car", "Zed", "z", "CAR"];
+        let sequential = Trie::from_iter_with(AsciiLower, words.clone());
+        let parallel = Trie::from_par_iter_with(AsciiLower, words.clone());
+        assert!(parallel == sequential);
+        assert_eq!(parallel.len(), sequential.len());
+        assert_eq!(parallel.iter().collect::<Vec<_>>(), sequential.iter().collect::<Vec<_>>());
+        assert_eq!(parallel.tree().to_string(), sequential.tree().to_string());
+        assert_eq!(parallel.frequency("car"), 3);
+        assert_eq!(parallel.frequency(""), 1);
+
+        // into a trie already holding elements
+        let mut extended = Trie::from_iter_with(AsciiLower, vec!["cart", "yak"]);
+        extended.par_extend(words.clone());
+        let mut expected = Trie::from_iter_with(AsciiLower, vec!["cart", "yak"]);
+        expected.extend(words);
+        assert!(extended == expected);
+        assert_eq!(extended.len(), expected.len());
+        assert_eq!(extended.frequency("cart"), 2);
+        assert_eq!(extended.tree().to_string(), expected.tree().to_string());
+
+        let mut fanout = Trie::with_root_fanout(Bytes);
+        fanout.par_extend((0..1000_u32).collect::<Vec<_>>());
+        assert_eq!(fanout.len(), 1000);
+        assert!((0..1000_u32).all(|key| fanout.contains(key)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_trie_serde_round_trip() {
+        let index_fn = |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize);
+        let mut trie = Trie::from_fn(index_fn, ('z' as usize) - ('a' as usize) + 1);
+        trie.extend(vec!["asd", "asx", "as", "dsa"]);
+
+        let json = serde_json::to_string(&trie).unwrap();
+        let data: SerializedTrie<char> = serde_json::from_str(&json).unwrap();
+        let restored = Trie::from_serialized(FnAlphabet::new(index_fn, ('z' as usize) - ('a' as usize) + 1), data).unwrap();
+        assert_eq!(restored.len(), 4);
+        for key in &["asd", "asx", "as", "dsa"] {
+            assert!(restored.contains(*key));
+        }
+        assert!(!restored.contains("a"));
+
+        let data: SerializedTrie<char> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            Trie::from_serialized(FnAlphabet::new(index_fn, 27), data).err(),
+            Some(TrieError::AlphabetMismatch { expected: 27, got: 26 }),
+        );
+
+        // payloads are checked rather than trusted, a wrong count or slots that do not fit the alphabet
+        // failing to load instead of misreporting `len` or panicking on a later insert
+        let malformed = |json: &str| {
+            let data: SerializedTrie<char> = serde_json::from_str(json).unwrap();
+            matches!(Trie::from_serialized(FnAlphabet::new(index_fn, 26), data), Err(TrieError::Malformed { .. }))
+        };
+        assert!(malformed(&json.replace("\"count\":4", "\"count\":5")));
+        assert!(malformed(&json.replacen("[null,", "[", 1)));
+        assert!(malformed(&json.replace("\"a\"", "\"b\"")));
+        assert!(serde_json::from_str::<SerializedTrie<char>>(&json.replacen("\"occupied\":2", "\"occupied\":3", 1)).is_err());
+    }
+
+    #[test]
+    fn test_trie_keys_with_prefix() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.extend(vec!["asdfg", "asdfh", "asd", "dsa"]);
+        let with_prefix = |prefix: &str| trie.keys_with_prefix(prefix).map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+
+        assert_eq!(with_prefix("as"), vec!["asd", "asdfg", "asdfh"]);
+        assert_eq!(with_prefix("asd"), vec!["asd", "asdfg", "asdfh"]);
+        assert_eq!(with_prefix("asdf"), vec!["asdfg", "asdfh"]);
+        assert_eq!(with_prefix("asdfh"), vec!["asdfh"]);
+        assert_eq!(with_prefix("d"), vec!["dsa"]);
+        assert_eq!(with_prefix(""), vec!["asd", "asdfg", "asdfh", "dsa"]);
+        assert!(with_prefix("asx").is_empty());
+        assert!(with_prefix("asdfgh").is_empty());
+        assert!(with_prefix("q").is_empty());
+    }
+
+    #[test]
+    fn test_trie_starts_with() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        assert!(!trie.starts_with(""));
+        trie.insert("asd");
+
+        assert!(trie.starts_with("as"));
+        assert!(!trie.contains("as"));
+        assert!(trie.starts_with("a"));
+        assert!(trie.starts_with("asd"));
+        assert!(trie.starts_with(""));
+        assert!(!trie.starts_with("asdf"));
+        assert!(!trie.starts_with("ax"));
+        assert!(!trie.starts_with("d"));
+    }
+
+    #[test]
+    fn test_trie_longest_prefix_match() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        assert_eq!(trie.longest_prefix_match("abc"), None);
+        trie.extend(vec!["a", "ab", "abcd"]);
+        let longest = |query: &str| trie.longest_prefix_match(query).map(|parts| parts.into_iter().collect::<String>());
+
+        assert_eq!(longest("abcx"), Some("ab".to_string()));
+        assert_eq!(longest("abc"), Some("ab".to_string()));
+        assert_eq!(longest("abcd"), Some("abcd".to_string()));
+        assert_eq!(longest("abcdefg"), Some("abcd".to_string()));
+        assert_eq!(longest("ax"), Some("a".to_string()));
+        assert_eq!(longest("b"), None);
+        assert_eq!(longest(""), None);
+    }
+
+    #[test]
+    fn test_trie_split_off_prefix() {
+        let words = |trie: &Trie<char, AsciiLower>| trie.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+        let mut trie = Trie::from_iter_with(AsciiLower, vec!["Apple", "a", "apply", "ant", "b", "banana", "band", "apple"]);
+
+        // split off elements keep the parts stored for the prefix
+        let split = trie.split_off_prefix("a").unwrap();
+        assert_eq!(words(&split), vec!["A", "Ant", "Apple", "Apply"]);
+        assert_eq!(split.len(), 4);
+        assert_eq!(split.frequency("apple"), 2);
+        assert_eq!(words(&trie), vec!["b", "banana", "band"]);
+        assert_eq!(trie.len(), 3);
+        assert_eq!(trie.tree().to_string(), Trie::from_iter_with(AsciiLower, vec!["b", "banana", "band"]).tree().to_string());
+
+        assert!(trie.split_off_prefix("c").is_none());
+        assert!(trie.split_off_prefix("bandana").is_none());
+        assert_eq!(trie.len(), 3);
+
+        // "ban" ends inside the run shared by "banana" and "band"
+        let split = trie.split_off_prefix("BAN").unwrap();
+        assert_eq!(words(&split), vec!["banana", "band"]);
+        assert_eq!(words(&trie), vec!["b"]);
+        assert_eq!(trie.tree().to_string(), Trie::from_iter_with(AsciiLower, vec!["b"]).tree().to_string());
+
+        let split = trie.split_off_prefix("").unwrap();
+        assert_eq!(words(&split), vec!["b"]);
+        assert!(trie.is_empty());
+        assert!(trie.split_off_prefix("").is_none());
+    }
+
+    #[test]
+    fn test_trie_iter_to_depth() {
+        let paths = |trie: &Trie<char, AsciiLower>, max_depth| trie.iter_to_depth(max_depth)
+            .map(|(parts, cut)| (parts.into_iter().collect::<String>(), cut))
+            .collect::<Vec<_>>();
+        let mut trie = Trie::new(AsciiLower);
+        assert!(paths(&trie, 2).is_empty());
+
+        trie.extend(vec!["a", "ab", "abcdefgh", "abxyz", "b", "bcdefghij", "qwertyuiop", "zz", "zzz"]);
+        assert_eq!(paths(&trie, 2), vec![
+            ("a".to_string(), false),
+            ("ab".to_string(), true),
+            ("b".to_string(), false),
+            ("bc".to_string(), true),
+            ("qw".to_string(), true),
+            ("zz".to_string(), true),
+        ]);
+        assert_eq!(paths(&trie, 0), vec![(String::new(), true)]);
+        assert_eq!(paths(&trie, 5), vec![
+            ("a".to_string(), false),
+            ("ab".to_string(), false),
+            ("abcde".to_string(), true),
+            ("abxyz".to_string(), false),
+            ("b".to_string(), false),
+            ("bcdef".to_string(), true),
+            ("qwert".to_string(), true),
+            ("zz".to_string(), false),
+            ("zzz".to_string(), false),
+        ]);
+        assert_eq!(
+            paths(&trie, 10).into_iter().map(|(path, _)| path).collect::<Vec<_>>(),
+            trie.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>()
+        );
+        assert!(paths(&trie, 10).iter().all(|(_, cut)| !cut));
+    }
+
+    #[test]
+    fn test_trie_front_coded_iter() {
+        let coded = |trie: &Trie<char, AsciiLower>| trie.front_coded_iter()
+            .map(|(shared, suffix)| (shared, suffix.into_iter().collect::<String>()))
+            .collect::<Vec<_>>();
+        let mut trie = Trie::new(AsciiLower);
+        assert!(coded(&trie).is_empty());
+
+        let words = vec!["car", "", "cart", "carbon", "cat", "dog", "do", "zebra", "carbonate"];
+        trie.extend(words.clone());
+        assert_eq!(coded(&trie), vec![
+            (0, String::new()),
+            (0, "car".to_string()),
+            (3, "bon".to_string()),
+            (6, "ate".to_string()),
+            (3, "t".to_string()),
+            (2, "t".to_string()),
+            (0, "do".to_string()),
+            (2, "g".to_string()),
+            (0, "zebra".to_string()),
+        ]);
+
+        // front coding round trips every element
+        let mut last = String::new();
+        let mut decoded = Vec::new();
+        for (shared, suffix) in coded(&trie) {
+            last.truncate(shared);
+            last.push_str(&suffix);
+            decoded.push(last.clone());
+        }
+        assert_eq!(decoded, trie.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>());
+        let mut sorted = words;
+        sorted.sort_unstable();
+        assert_eq!(decoded, sorted);
+    }
+
+    #[test]
+    fn test_trie_remove_prefix() {
+        let mut trie = Trie::from_iter_with(AsciiLower, vec!["car", "cart", "carbon", "care", "cat", "dog", "ca", "car"]);
+        assert_eq!(trie.len(), 7);
+
+        assert_eq!(trie.remove_prefix("dot"), 0);
+        assert_eq!(trie.remove_prefix("cars"), 0);
+        assert_eq!(trie.remove_prefix("e"), 0);
+        assert_eq!(trie.len(), 7);
+
+        // "carb" ends inside the run of "carbon"
+        assert_eq!(trie.remove_prefix("carb"), 1);
+        assert!(!trie.contains("carbon"));
+        assert_eq!(trie.len(), 6);
+
+        assert_eq!(trie.remove_prefix("car"), 3);
+        assert_eq!(trie.len(), 3);
+        assert!(trie.contains("ca"));
+        assert!(trie.contains("cat"));
+        assert!(!trie.contains("car"));
+        assert_eq!(trie.tree().to_string(), Trie::from_iter_with(AsciiLower, vec!["ca", "cat", "dog"]).tree().to_string());
+
+        assert_eq!(trie.remove_prefix(""), 3);
+        assert!(trie.is_empty());
+        assert_eq!(trie.remove_prefix(""), 0);
+    }
+
+    #[test]
+    fn test_trie_common_prefix() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+        let common = |trie: &Trie<char, _>| trie.common_prefix().into_iter().collect::<String>();
+
+        assert_eq!(common(&trie), "");
+        trie.insert("interview");
+        assert_eq!(common(&trie), "interview");
+        trie.insert("internet");
+        assert_eq!(common(&trie), "inter");
+        trie.insert("interval");
+        assert_eq!(common(&trie), "inter");
+        trie.insert("into");
+        assert_eq!(common(&trie), "int");
+        // an element ending mid descent is the longest prefix anything can share with it
+        trie.insert("in");
+        assert_eq!(common(&trie), "in");
+        trie.insert("zebra");
+        assert_eq!(common(&trie), "");
+
+        trie.remove("zebra");
+        trie.remove("in");
+        assert_eq!(common(&trie), "int");
+
+        let mut fanout = Trie::with_root_fanout(AsciiLower);
+        fanout.extend(vec!["abc", "abd"]);
+        assert_eq!(fanout.common_prefix(), vec!['a', 'b']);
+        fanout.insert("");
+        assert!(fanout.common_prefix().is_empty());
+    }
+
+    #[test]
+    fn test_trie_count_prefix() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        assert_eq!(trie.count_prefix(""), 0);
+        trie.extend(vec!["car", "card", "care", "careful", "cat", "dog"]);
+
+        assert_eq!(trie.count_prefix(""), 6);
+        assert_eq!(trie.count_prefix("c"), 5);
+        assert_eq!(trie.count_prefix("car"), 4);
+        assert_eq!(trie.count_prefix("care"), 2);
+        assert_eq!(trie.count_prefix("caref"), 1);
+        assert_eq!(trie.count_prefix("cat"), 1);
+        assert_eq!(trie.count_prefix("cab"), 0);
+        assert_eq!(trie.count_prefix("dogs"), 0);
+
+        trie.remove("card");
+        assert_eq!(trie.count_prefix("car"), 3);
+    }
+
+    #[test]
+    fn test_trie_top_k_completions() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        for &(word, times) in &[("car", 3), ("card", 1), ("care", 5), ("cart", 3), ("cat", 2), ("dog", 9)] {
+            for _ in 0..times {
+                trie.insert(word);
+            }
+        }
+        assert_eq!(trie.len(), 6);
+        assert_eq!(trie.frequency("care"), 5);
+        assert_eq!(trie.frequency("ca"), 0);
+        let top = |prefix: &str, k: usize| trie.top_k_completions(prefix, k).into_iter()
+            .map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+
+        assert_eq!(top("car", 2), vec!["care", "car"]);
+        // ties are broken lexicographically, fewer completions than k are all returned
+        assert_eq!(top("car", 10), vec!["care", "car", "cart", "card"]);
+        assert_eq!(top("c", 3), vec!["care", "car", "cart"]);
+        assert_eq!(top("", 1), vec!["dog"]);
+        assert!(top("car", 0).is_empty());
+        assert!(top("x", 3).is_empty());
+    }
+
+    #[test]
+    fn test_trie_fuzzy_contains() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.extend(vec!["kitten", "kitchen", "sitten", "mitten", "kit", "sitting", "bitte"]);
+        let fuzzy = |query: &str, max_distance: usize| trie.fuzzy_contains(query, max_distance).into_iter()
+            .map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+
+        assert_eq!(fuzzy("kitten", 0), vec!["kitten"]);
+        // substitution, deletion and insertion
+        assert_eq!(fuzzy("kitten", 1), vec!["kitten", "mitten", "sitten"]);
+        assert_eq!(fuzzy("kittens", 1), vec!["kitten"]);
+        assert_eq!(fuzzy("kiten", 1), vec!["kitten"]);
+        assert_eq!(fuzzy("kitten", 2), vec!["bitte", "kitchen", "kitten", "mitten", "sitten"]);
+        assert_eq!(fuzzy("", 3), vec!["kit"]);
+        assert!(fuzzy("dog", 1).is_empty());
+    }
+
+    #[test]
+    fn test_trie_matches_pattern() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.extend(vec!["cat", "cot", "cut", "cats", "ct", "cab", "dot"]);
+        type Slot = Option<fn(&char) -> bool>;
+        let matching = |pattern: &[Slot]| trie.matches_pattern(pattern).into_iter()
+            .map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>();
+
+        // "c_t"
+        assert_eq!(matching(&[Some(|c| *c == 'c'), None, Some(|c| *c == 't')]), vec!["cat", "cot", "cut"]);
+        assert_eq!(matching(&[None, Some(|c| *c == 'o'), None]), vec!["cot", "dot"]);
+        assert_eq!(matching(&[Some(|c| *c == 'c'), Some(|c| *c != 'u'), None]), vec!["cab", "cat", "cot"]);
+        assert_eq!(matching(&[None, None, None, None]), vec!["cats"]);
+        assert_eq!(matching(&[None, None]), vec!["ct"]);
+        assert!(matching(&[]).is_empty());
+        assert!(matching(&[None, None, None, None, None]).is_empty());
+    }
+
+    #[test]
+    fn test_trie_clear() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.insert("asd");
+        trie.insert("asx");
+        trie.insert("dsa");
+        trie.clear();
+        assert!(trie.is_empty());
+        assert!(!trie.contains("asd"));
+        assert_eq!(trie.iter().count(), 0);
+
+        trie.insert("zzz");
+        assert!(trie.contains("zzz"));
+        assert_eq!(trie.len(), 1);
+    }
+
+    #[test]
+    fn test_trie_remove() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.insert(String::from("asd"));
+        trie.insert(String::from("dsa"));
+        assert!(trie.remove(String::from("asd")));
+        assert!(!trie.contains(String::from("asd")));
+        assert!(trie.contains(String::from("dsa")));
+        assert!(!trie.remove(String::from("asd")));
+        assert!(trie.remove(String::from("dsa")));
+        assert!(!trie.contains(String::from("dsa")));
+    }
+
+    #[test]
+    fn test_trie_remove_merges_shared_prefix() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.insert(String::from("asdf"));
+        trie.insert(String::from("asxy"));
+        assert!(trie.remove(String::from("asxy")));
+        assert!(trie.contains(String::from("asdf")));
+        assert!(!trie.contains(String::from("asxy")));
+
+        // the surviving branch is merged back into a single run
+        match &trie.root {
+            radix_tree::Node::Compressed { compressed, child, .. } => {
+                assert_eq!(compressed, &vec!['a', 's', 'd', 'f']);
+                assert!(matches!(**child, radix_tree::Node::Leaf(_)));
+            }
+            _ => panic!("expected a single compressed run"),
+        }
+    }
+
+    #[test]
+    fn test_trie_adjacent_runs() {
+        use radix_tree::Node;
+
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        // a non terminal run directly under another is not built by the trie, but is still walked
+        // and merged back into a single run rather than rejected
+        trie.insert("abcd");
+        trie.root = Node::Compressed {
+            compressed: vec!['a', 'b'],
+            child: Box::new(Node::Compressed { compressed: vec!['c', 'd'], child: Box::new(Node::Leaf(1)), value: None }),
+            value: None,
+        };
+        assert!(trie.contains("abcd"));
+        assert!(!trie.contains("abc"));
+        assert!(trie.insert("abce"));
+        assert!(trie.insert("a"));
+        assert_eq!(trie.iter().map(|parts| parts.into_iter().collect::<String>()).collect::<Vec<_>>(), vec!["a", "abcd", "abce"]);
+
+        assert!(trie.remove("abce"));
+        assert!(trie.remove("a"));
+        match &trie.root {
+            Node::Compressed { compressed, child, value: None } => {
+                assert_eq!(compressed, &vec!['a', 'b', 'c', 'd']);
+                assert!(matches!(**child, Node::Leaf(_)));
+            }
+            _ => panic!("expected a single compressed run"),
+        }
+    }
+
+    #[test]
+    fn test_trie_prefix_members() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.insert(String::from("asd"));
+        assert!(!trie.contains(String::from("as")));
+        assert!(!trie.contains(String::from("asdf")));
+        trie.insert(String::from("as"));
+        assert!(trie.contains(String::from("as")));
+        assert!(trie.contains(String::from("asd")));
+        assert!(!trie.contains(String::from("a")));
+
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.insert(String::from("as"));
+        assert!(!trie.contains(String::from("asd")));
+        trie.insert(String::from("asd"));
+        trie.insert(String::from("asx"));
+        assert!(trie.contains(String::from("as")));
+        assert!(trie.contains(String::from("asd")));
+        assert!(trie.contains(String::from("asx")));
+        assert!(!trie.contains(String::from("asdf")));
+        assert_eq!(trie.len(), 3);
+    }
+
+    #[test]
+    fn test_trie_contains_past_compressed_run() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.insert("asd");
+        trie.insert("asdf");
+        assert!(trie.contains("asd"));
+        assert!(!trie.contains("asde"));
+        assert!(trie.contains("asdf"));
+        assert!(!trie.contains("asdfg"));
+
+        trie.insert("asdx");
+        assert!(trie.contains("asd"));
+        assert!(!trie.contains("asde"));
+        assert!(trie.contains("asdf"));
+        assert!(trie.contains("asdx"));
+        assert!(!trie.contains("asdxa"));
+    }
+
+    #[test]
+    fn test_trie_remove_prefix_member() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        trie.insert(String::from("asd"));
+        trie.insert(String::from("as"));
+        trie.insert(String::from("asdfg"));
+        assert!(trie.remove(String::from("asd")));
+        assert!(!trie.remove(String::from("asdf")));
+        assert!(trie.contains(String::from("as")));
+        assert!(trie.contains(String::from("asdfg")));
+        assert!(trie.remove(String::from("as")));
+        assert!(trie.contains(String::from("asdfg")));
+
+        // with no terminals left along the way everything is merged back into a single run
+        match &trie.root {
+            radix_tree::Node::Compressed { compressed, child, value: None } => {
+                assert_eq!(compressed.len(), 5);
+                assert!(matches!(**child, radix_tree::Node::Leaf(_)));
+            }
+            _ => panic!("expected a single compressed run"),
+        }
+    }
+
+    #[test]
+    fn test_trie_remove_non_member() {
+        let mut trie = Trie::from_fn(
+            |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
+            ('z' as usize) - ('a' as usize) + 1,
+        );
+
+        assert!(!trie.remove(String::from("asd")));
+        trie.insert(String::from("asdf"));
+        trie.insert(String::from("asxy"));
+        assert!(!trie.remove(String::from("asq")));
+        assert!(!trie.remove(String::from("qwe")));
+        assert!(!trie.remove(String::from("asdfg")));
+        assert!(trie.contains(String::from("asdf")));
+        assert!(trie.contains(String::from("asxy")));
     }
 }
@@ -1,218 +1,2822 @@
-/// A generic tree based collection storing decomposed items
-///
-/// A generic tree based fixed width per node tree in which inserted elements are decomposed into
-/// their parts and stored such that shared prefixes are reused. Optimization used for nodes with
-/// single child such that nodes until a future split are condensed into a single node.
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BinaryHeap};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::{Ordering, Reverse};
+use core::borrow::Borrow;
+use core::fmt;
+use core::fmt::Write;
+use core::iter::Peekable;
+use core::marker::PhantomData;
+use core::mem;
+
+use super::children::{self, Children};
+use super::{Alphabet, AsciiLower, Binary, Bytes, Decomposable, DecomposableRef, DecomposeRef, FnAlphabet, SkipFnAlphabet, TerminatedTrie, TrieError};
+
+/// a node in the tree, representing the position reached after consuming some prefix of parts
 ///
-/// AKA "prefix tree", "trie"
+/// `value` holds the value of a stored element ending at the position of the node itself, `Leaf`
+/// is such a terminal position without anything stored below it.
 ///
-/// # Examples
+/// a `Compressed` node's child is never `Empty`, and is only `Compressed` if the child is terminal,
+/// otherwise both runs are merged into one. this is kept by building runs through `new_compressed`
+/// rather than asserted, the node methods walk any nesting of runs and restore it where they
+/// rebuild a run. a `Normal` node has at least two children, other than a root made by
+/// `Trie::with_root_fanout` and the chained nodes of a run kept short of the trie's
+/// `min_compress_len`.
 ///
-/// ```
-/// let mut trie = Trie::new(
-///     |c: &char| (c.to_lowercase().next().unwrap() as usize) - ('a' as usize),
-///     ('z' as usize) - ('a' as usize),
-/// );
-/// assert_eq!(trie.contains(&"asd".to_string()), false);
-/// trie.insert("asd".to_string());
-/// assert_eq!(trie.contains(&"asd".to_string()), true);
-/// ```
+/// runs are allocated to fit, such that tries holding the same elements take the same memory
+/// whatever the order of insertions, removals and merges building them.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum Node<T, V> {
+    Empty,
+    Leaf(V),
+    Normal {
+        children: Children<T, V>,
+        #[cfg_attr(feature = "serde", serde(with = "optional_value"))]
+        value: Option<V>,
+    },
+    Compressed {
+        compressed: Vec<T>,
+        child: Box<Node<T, V>>,
+        #[cfg_attr(feature = "serde", serde(with = "optional_value"))]
+        value: Option<V>,
+    },
+}
 
-use std::mem;
+/// (de)serializes a node's value as a sequence of zero or one values, as self describing formats
+/// would otherwise write a `Some(())` the same as `None`
+#[cfg(feature = "serde")]
+mod optional_value {
+    use alloc::vec::Vec;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use super::Decomposable;
+    pub(crate) fn serialize<V: Serialize, S: Serializer>(value: &Option<V>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(value.iter())
+    }
 
-enum Node<T> {
-    Empty,
-    Normal(Vec<Node<T>>),
-    Compressed { compressed: Vec<T>, child: Box<Node<T>> },
+    pub(crate) fn deserialize<'de, V: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<Option<V>, D::Error> {
+        Ok(Vec::<V>::deserialize(deserializer)?.into_iter().next())
+    }
 }
 
-impl<T> Node<T> {
-    fn new_empty() -> Node<T> {
+impl<T, V> Node<T, V> {
+    pub(crate) fn new_empty() -> Node<T, V> {
         Node::Empty
     }
 
-    fn new_compressed<TIt: Iterator<Item=T>>(it: TIt) -> Node<T> {
-        let compressed = it.collect::<Vec<_>>();
-        let child = Box::new(Node::Empty);
+    /// builds a run of `it` in front of `child`, merging with `child` if it is itself a non terminal run
+    ///
+    /// the run is allocated up front for the lower bound of `it`'s size hint, exact for
+    /// decompositions backed by a slice or `Vec`
+    fn new_compressed<TIt: Iterator<Item=T>>(it: TIt, child: Node<T, V>) -> Node<T, V> {
+        let mut compressed = it.collect::<Vec<_>>();
+        if compressed.is_empty() || child.is_empty() {
+            return child;
+        }
+
+        let child = match child {
+            Node::Compressed { compressed: tail, child, value: None } => {
+                // exactly, as spare capacity would only be shrunk away below
+                compressed.reserve_exact(tail.len());
+                compressed.extend(tail);
+                child
+            }
+            child => Box::new(child),
+        };
+        compressed.shrink_to_fit();
 
-        Node::Compressed { compressed, child }
+        Node::Compressed { compressed, child, value: None }
     }
 
-    fn new_normal(positions_and_nodes: Vec<(usize, Node<T>)>, alphabet_size: usize) -> Node<T> {
-        let mut children = Vec::with_capacity(alphabet_size);
-        for _ in 0..alphabet_size {
-            children.push(Node::Empty);
+    /// builds a run of `it` in front of `child` as `new_compressed` does, unless it is shorter than
+    /// `min_compress_len` in which case every part gets a `Normal` node of its own
+    ///
+    /// a run merging with a run of `child` is compressed whatever its length
+    fn new_run<TIt, FIndex>(it: TIt, child: Node<T, V>, index_fn: &FIndex, alphabet_size: usize, min_compress_len: usize) -> Node<T, V>
+        where TIt: Iterator<Item=T>, FIndex: Fn(&T) -> usize {
+        if min_compress_len <= 1 {
+            return Node::new_compressed(it, child);
         }
+        let parts = it.collect::<Vec<_>>();
+        if parts.len() >= min_compress_len || child.is_empty() || matches!(child, Node::Compressed { value: None, .. }) {
+            return Node::new_compressed(parts.into_iter(), child);
+        }
+        parts.into_iter().rev().fold(child, |node, part| Node::new_normal(vec![(index_fn(&part), part, node)], alphabet_size))
+    }
 
-        for (pos, node) in positions_and_nodes {
-            children[pos] = node;
+    fn new_normal<I: IntoIterator<Item=(usize, T, Node<T, V>)>>(positions_and_nodes: I, alphabet_size: usize) -> Node<T, V> {
+        let mut children = Children::new(alphabet_size);
+        for (pos, part, node) in positions_and_nodes {
+            children.insert(pos, part, node);
         }
 
-        Node::Normal(children)
+        Node::Normal { children, value: None }
     }
-}
 
-pub struct Trie<TParts, FIndex: Fn(&TParts) -> usize> {
-    root: Node<TParts>,
-    index_fn: FIndex,
-    alphabet_size: usize,
-}
+    /// sets the value of the element ending at the position of this node
+    fn with_value(self, new_value: Option<V>) -> Node<T, V> {
+        match (self, new_value) {
+            (Node::Empty, None) | (Node::Leaf(_), None) => Node::Empty,
+            (Node::Empty, Some(value)) | (Node::Leaf(_), Some(value)) => Node::Leaf(value),
+            (Node::Normal { children, .. }, value) => Node::Normal { children, value },
+            (Node::Compressed { compressed, child, .. }, value) => Node::Compressed { compressed, child, value },
+        }
+    }
 
-impl<TParts, FIndex: Fn(&TParts) -> usize> Trie<TParts, FIndex> {
-    pub fn new(index_fn: FIndex, alphabet_size: usize) -> Trie<TParts, FIndex> {
-        let new_node = Node::new_empty();
-        Trie { root: new_node, index_fn, alphabet_size }
+    pub(crate) fn is_empty(&self) -> bool {
+        matches!(self, Node::Empty)
     }
 
-    pub fn insert<TIt: Iterator<Item=TParts>, T: Decomposable<TParts, TIt>>(&mut self, t: T) {
-        enum EitherIt<TItem, TIt1: Iterator<Item=TItem>, TIt2: Iterator<Item=TItem>> {
-            First(TIt1),
-            Second(TIt2),
+    /// the value of the element ending at the position of this node
+    pub(crate) fn value(&self) -> Option<&V> {
+        match self {
+            Node::Empty => None,
+            Node::Leaf(value) => Some(value),
+            Node::Normal { value, .. } | Node::Compressed { value, .. } => value.as_ref(),
         }
-        impl<TItem, TIt1: Iterator<Item=TItem>, TIt2: Iterator<Item=TItem>> Iterator for EitherIt<TItem, TIt1, TIt2> {
-            type Item = TItem;
+    }
+
+    /// stores `new_value` at the end of the path of `it`, returns the value previously stored there
+    pub(crate) fn insert<TIt, FIndex>(&mut self, it: &mut Peekable<TIt>, new_value: V, index_fn: &FIndex, alphabet_size: usize) -> Option<V>
+        where TIt: Iterator<Item=T>, FIndex: Fn(&T) -> usize {
+        // maps always compress their runs
+        self.upsert(it, new_value, mem::replace, index_fn, alphabet_size, 1)
+    }
 
-            fn next(&mut self) -> Option<<Self as Iterator>::Item> {
-                match self {
-                    EitherIt::First(it) => it.next(),
-                    EitherIt::Second(it) => it.next(),
+    /// stores `new_value` at the end of the path of `it` if nothing is stored there yet, otherwise
+    /// hands the stored value along with `new_value` to `update`, returning what it returns
+    ///
+    /// runs built for the new element are only compressed from `min_compress_len` parts on
+    pub(crate) fn upsert<TIt, FIndex, FUpdate>(&mut self, it: &mut Peekable<TIt>, new_value: V, update: FUpdate, index_fn: &FIndex, alphabet_size: usize, min_compress_len: usize) -> Option<V>
+        where TIt: Iterator<Item=T>, FIndex: Fn(&T) -> usize, FUpdate: FnOnce(&mut V, V) -> V {
+        match self {
+            Node::Empty => {
+                *self = Node::new_run(it, Node::Leaf(new_value), index_fn, alphabet_size, min_compress_len);
+                None
+            }
+            Node::Leaf(value) => {
+                if it.peek().is_none() {
+                    return Some(update(value, new_value));
                 }
+                if let Node::Leaf(value) = mem::replace(self, Node::Empty) {
+                    *self = Node::new_run(it, Node::Leaf(new_value), index_fn, alphabet_size, min_compress_len).with_value(Some(value));
+                }
+                None
             }
-        }
-
-        let mut stack = vec![(&mut self.root, EitherIt::First(t.decompose()))];
-
-        while let Some((current, mut it)) = stack.pop() {
-            match current {
-                Node::Empty => {
-                    let compressed = it.collect::<Vec<_>>();
-                    if !compressed.is_empty() {
-                        let child = Box::new(Node::Empty);
-                        let new = Node::Compressed { compressed, child };
-                        mem::replace(current, new);
+            Node::Normal { children, value } => {
+                if let Some(part) = it.next() {
+                    let pos = index_fn(&part);
+                    match children.get_mut(pos) {
+                        Some((_, child)) => child.upsert(it, new_value, update, index_fn, alphabet_size, min_compress_len),
+                        None => {
+                            children.insert(pos, part, Node::new_run(it, Node::Leaf(new_value), index_fn, alphabet_size, min_compress_len));
+                            None
+                        }
                     }
+                } else {
+                    update_value(value, new_value, update)
                 }
-                Node::Normal(ref mut children) => {
-                    if let Some(part) = it.next() {
-                        let pos = (self.index_fn)(&part);
-                        stack.push((&mut children[pos], it));
-                    }
-                }
-                Node::Compressed { ref mut compressed, child } => {
-                    let mut current_pos = 0;
-                    'compressed: loop {
-                        if let Some(new_part) = it.next() {
-                            if current_pos == compressed.len() {
-                                match **child {
-                                    Node::Empty => {
-                                        compressed.push(new_part);
-                                        compressed.extend(it);
-                                        compressed.shrink_to_fit()
-                                    }
-                                    Node::Normal(ref mut children) => {
-                                        let pos = (self.index_fn)(&new_part);
-                                        stack.push((&mut children[pos], it));
-                                    }
-                                    Node::Compressed { .. } => panic!()
-                                }
-                                break 'compressed;
-                            } else {
-                                let existing_part = &compressed[current_pos];
-                                let pos_existing = (self.index_fn)(existing_part);
-                                let pos_new = (self.index_fn)(&new_part);
-
-                                if pos_existing != pos_new {
-                                    match **child {
-                                        Node::Empty => {
-                                            let new_compressed = Node::new_compressed(it);
-
-                                            let mut drain = compressed.drain(current_pos..);
-                                            drain.next();
-                                            let existing_compressed = Node::new_compressed(drain);
-
-                                            let new_node = Node::new_normal(vec![(pos_new, new_compressed), (pos_existing, existing_compressed)], self.alphabet_size);
-                                            mem::replace(child, Box::new(new_node));
-                                        }
-                                        Node::Normal(ref mut children) => {
-                                            let mut drain = compressed.drain(current_pos..);
-                                            drain.next();
-
-                                            let (min_pos, max_pos, left_it, right_it) = if pos_existing > pos_new {
-                                                (pos_new, pos_existing, it, EitherIt::Second(drain))
-                                            } else {
-                                                (pos_existing, pos_new, EitherIt::Second(drain), it)
-                                            };
-
-                                            let (left, right) = children.split_at_mut(max_pos);
-                                            stack.push((&mut left[min_pos], left_it));
-                                            stack.push((&mut right[0], right_it));
-                                        }
-                                        Node::Compressed { .. } => panic!()
-                                    }
-                                    break 'compressed;
-                                } else {}
-                            }
+            }
+            Node::Compressed { compressed, child, value } => {
+                for current_pos in 0..compressed.len() {
+                    let pos_existing = index_fn(&compressed[current_pos]);
+                    let pos_new = match it.peek() {
+                        Some(new_part) => index_fn(new_part),
+                        None if current_pos == 0 => return update_value(value, new_value, update),
+                        None => {
+                            // element ends inside the run, split it such that the tail starts at a terminal
+                            let tail = compressed.split_off(current_pos);
+                            compressed.shrink_to_fit();
+                            let old_child = mem::replace(&mut **child, Node::Empty);
+                            **child = Node::Compressed { compressed: tail, child: Box::new(old_child), value: Some(new_value) };
+                            return None;
+                        }
+                    };
+
+                    if pos_existing != pos_new {
+                        let new_part = it.next().unwrap();
+                        let mut drain = compressed.split_off(current_pos).into_iter();
+                        let existing_part = drain.next().unwrap();
+                        let existing_child = Node::new_run(drain, mem::replace(&mut **child, Node::Empty), index_fn, alphabet_size, min_compress_len);
+                        let new_child = Node::new_run(it, Node::Leaf(new_value), index_fn, alphabet_size, min_compress_len);
+
+                        let new_node = Node::new_normal(
+                            vec![(pos_existing, existing_part, existing_child), (pos_new, new_part, new_child)],
+                            alphabet_size,
+                        );
+                        if compressed.is_empty() {
+                            *self = new_node.with_value(value.take());
                         } else {
-                            break 'compressed;
+                            compressed.shrink_to_fit();
+                            **child = new_node;
                         }
-                        current_pos += 1;
+                        return None;
                     }
+                    it.next();
                 }
+
+                child.upsert(it, new_value, update, index_fn, alphabet_size, min_compress_len)
             }
         }
     }
 
-    pub fn contains<TIt: Iterator<Item=TParts>, T: Decomposable<TParts, TIt>>(&self, t: T) -> bool {
-        let mut current = &self.root;
-        let mut it = t.decompose();
+    /// returns the value stored at the end of the path of `it`
+    ///
+    /// parts of `it` may be borrowed, they are only indexed and never stored
+    pub(crate) fn get<Q, TIt, FIndex>(&self, it: TIt, index_fn: &FIndex) -> Option<&V>
+        where Q: Borrow<T>, TIt: Iterator<Item=Q>, FIndex: Fn(&T) -> usize {
+        let mut current = self;
+        let mut it = it.peekable();
         'parts_loop: loop {
             current = match current {
                 Node::Empty => {
-                    break 'parts_loop it.next().is_none();
+                    break 'parts_loop None;
                 }
-                Node::Normal(children) => {
+                Node::Leaf(value) => {
+                    // parts left over past a leaf belong to an element that is not stored
+                    break 'parts_loop if it.next().is_none() { Some(value) } else { None };
+                }
+                Node::Normal { children, value } => {
                     if let Some(part) = it.next() {
-                        let pos = (self.index_fn)(&part);
-                        current = &children[pos];
-                        current
+                        let pos = index_fn(part.borrow());
+                        match children.get(pos) {
+                            Some((_, child)) => child,
+                            None => break 'parts_loop None,
+                        }
                     } else {
-                        break 'parts_loop false;
+                        break 'parts_loop value.as_ref();
                     }
                 }
-                Node::Compressed { compressed, child } => {
+                Node::Compressed { compressed, child, value } => {
+                    if it.peek().is_none() {
+                        break 'parts_loop value.as_ref();
+                    }
                     for held_part in compressed.iter() {
-                        if let Some(part) = it.next() {
-                            if (self.index_fn)(held_part) != (self.index_fn)(&part) {
-                                break 'parts_loop false;
-                            }
-                        } else {
-                            break 'parts_loop false;
+                        match it.next() {
+                            Some(part) if index_fn(held_part) == index_fn(part.borrow()) => {}
+                            _ => break 'parts_loop None,
+                        }
+                    }
+                    // the whole run matched, the query continues (or ends) at the child
+                    child
+                }
+            }
+        }
+    }
+
+    /// finds the node below which every element starts with the parts of `it`, along with the stored
+    /// parts leading to that node
+    ///
+    /// a prefix ending inside a `Compressed` run continues to the run's child, the rest of the run
+    /// being shared by everything below
+    pub(crate) fn find_prefix<Q, TIt, FIndex>(&self, it: TIt, index_fn: &FIndex) -> Option<(Vec<&T>, &Node<T, V>)>
+        where Q: Borrow<T>, TIt: Iterator<Item=Q>, FIndex: Fn(&T) -> usize {
+        let mut current = self;
+        let mut it = it.peekable();
+        let mut path = Vec::new();
+        loop {
+            if it.peek().is_none() {
+                return Some((path, current));
+            }
+            current = match current {
+                Node::Empty | Node::Leaf(_) => return None,
+                Node::Normal { children, .. } => {
+                    let part = it.next().unwrap();
+                    let (held_part, child) = children.get(index_fn(part.borrow()))?;
+                    path.push(held_part);
+                    child
+                }
+                Node::Compressed { compressed, child, .. } => {
+                    for held_part in compressed.iter() {
+                        match it.next() {
+                            Some(part) if index_fn(held_part) == index_fn(part.borrow()) => {}
+                            Some(_) => return None,
+                            None => break,
+                        }
+                    }
+                    path.extend(compressed.iter());
+                    child
+                }
+            }
+        }
+    }
+
+    /// whether any stored element is a prefix of the parts of `it`, stopping at the first terminal
+    /// node reached
+    pub(crate) fn has_prefix_of<TIt, FIndex>(&self, mut it: TIt, index_fn: &FIndex) -> bool
+        where TIt: Iterator<Item=T>, FIndex: Fn(&T) -> usize {
+        let mut current = self;
+        loop {
+            if current.value().is_some() {
+                return true;
+            }
+            current = match current {
+                Node::Empty | Node::Leaf(_) => return false,
+                Node::Normal { children, .. } => match it.next().and_then(|part| children.get(index_fn(&part))) {
+                    Some((_, child)) => child,
+                    None => return false,
+                },
+                Node::Compressed { compressed, child, .. } => {
+                    if !compressed.iter().all(|held_part| it.next().is_some_and(|part| index_fn(held_part) == index_fn(&part))) {
+                        return false;
+                    }
+                    child
+                }
+            }
+        }
+    }
+
+    /// finds the longest stored element which is a prefix of the parts of `it`, returning its stored
+    /// parts along with its value
+    pub(crate) fn longest_prefix<TIt, FIndex>(&self, it: TIt, index_fn: &FIndex) -> Option<(Vec<&T>, &V)>
+        where TIt: Iterator<Item=T>, FIndex: Fn(&T) -> usize {
+        let mut current = self;
+        let mut it = it.peekable();
+        let mut path = Vec::new();
+        let mut longest = None;
+        loop {
+            current = match current {
+                Node::Empty => break,
+                Node::Leaf(value) => {
+                    longest = Some((path.len(), value));
+                    break;
+                }
+                Node::Normal { children, value } => {
+                    if let Some(value) = value {
+                        longest = Some((path.len(), value));
+                    }
+                    let child = it.next().and_then(|part| children.get(index_fn(&part)));
+                    match child {
+                        Some((held_part, child)) => {
+                            path.push(held_part);
+                            child
                         }
+                        None => break,
+                    }
+                }
+                Node::Compressed { compressed, child, value } => {
+                    if let Some(value) = value {
+                        longest = Some((path.len(), value));
+                    }
+                    // members below the run are only prefixes of the query if it holds the whole run
+                    let matched = compressed.iter()
+                        .all(|held_part| it.next().is_some_and(|part| index_fn(held_part) == index_fn(&part)));
+                    if !matched {
+                        break;
+                    }
+                    path.extend(compressed.iter());
+                    child
+                }
+            }
+        }
+        longest.map(|(len, value)| {
+            path.truncate(len);
+            (path, value)
+        })
+    }
+
+    /// the stored parts shared by every element below this node
+    ///
+    /// descends while there is a single way down, stopping at a branch or at a node which is
+    /// itself an element, as nothing past it is shared with that element
+    pub(crate) fn common_prefix(&self) -> Vec<&T> {
+        let mut current = self;
+        let mut path = Vec::new();
+        loop {
+            current = match current {
+                Node::Normal { children, value: None } if children.len() == 1 => {
+                    let (_, part, child) = children.iter().next().unwrap();
+                    path.push(part);
+                    child
+                }
+                Node::Compressed { compressed, child, value: None } => {
+                    path.extend(compressed.iter());
+                    child
+                }
+                _ => return path,
+            }
+        }
+    }
+
+    /// the stored parts of the least element below this node in index order, descending into the
+    /// lowest child until reaching an element, which comes before everything below it
+    pub(crate) fn first(&self) -> Option<Vec<&T>> {
+        let mut current = self;
+        let mut path = Vec::new();
+        loop {
+            if current.value().is_some() {
+                return Some(path);
+            }
+            current = match current {
+                Node::Empty | Node::Leaf(_) => return None,
+                Node::Normal { children, .. } => {
+                    let (_, part, child) = children.iter().next()?;
+                    path.push(part);
+                    child
+                }
+                Node::Compressed { compressed, child, .. } => {
+                    path.extend(compressed.iter());
+                    child
+                }
+            }
+        }
+    }
+
+    /// the stored parts of the greatest element below this node in index order, descending into
+    /// the highest child for as long as there is one
+    pub(crate) fn last(&self) -> Option<Vec<&T>> {
+        let mut current = self;
+        let mut path = Vec::new();
+        loop {
+            current = match current {
+                Node::Empty => return None,
+                Node::Leaf(_) => return Some(path),
+                Node::Normal { children, value } => match children.iter().next_back() {
+                    Some((_, part, child)) => {
+                        path.push(part);
+                        child
                     }
+                    None => return value.as_ref().map(|_| path),
+                },
+                Node::Compressed { compressed, child, .. } => {
+                    path.extend(compressed.iter());
                     child
                 }
             }
         }
     }
 
-//    pub fn print_tree(&self) {
-//        Trie::<TParts, FIndex>::print_me(&self.root, 0);
-//    }
-//
-//    fn print_me(node: &Node<TParts>, indent: usize) {
-//        println!("{:indent$}{:?}", "", node, indent = indent);
-//        match node {
-//            Node::Normal(children) => {
-//                children.iter().filter(|c| !c.is_empty()).for_each(|c|
-//                    Trie::<TParts, FIndex>::print_me(c, indent + 2)
-//                )
-//            }
-//            Node::Compressed { compressed: _, child } => {
-//                Trie::<TParts, FIndex>::print_me(child, indent + 2);
-//            }
-//            _ => {}
-//        }
-//    }
+    /// number of elements stored at or below this node
+    pub(crate) fn count(&self) -> usize {
+        match self {
+            Node::Empty => 0,
+            Node::Leaf(_) => 1,
+            Node::Normal { children, value } => {
+                value.iter().count() + children.iter().map(|(_, _, child)| child.count()).sum::<usize>()
+            }
+            Node::Compressed { child, value, .. } => value.iter().count() + child.count(),
+        }
+    }
+
+    /// collects every element stored below this node within `max_distance` edits of the query
+    ///
+    /// `row` holds the edit distances between the parts of `path` and every prefix of the query,
+    /// given as the indices of its parts. branches are pruned once no distance of the row is within
+    /// `max_distance`, as descending further can only grow them
+    pub(crate) fn fuzzy<'a, FIndex>(&'a self, query: &[usize], max_distance: usize, index_fn: &FIndex, path: &mut Vec<&'a T>, row: &[usize], found: &mut Vec<Vec<&'a T>>)
+        where FIndex: Fn(&T) -> usize {
+        let within = |row: &[usize]| row.last().is_some_and(|&distance| distance <= max_distance);
+        match self {
+            Node::Empty => {}
+            Node::Leaf(_) => {
+                if within(row) {
+                    found.push(path.clone());
+                }
+            }
+            Node::Normal { children, value } => {
+                if value.is_some() && within(row) {
+                    found.push(path.clone());
+                }
+                for (_, part, child) in children.iter() {
+                    let next_row = edit_row(row, query, index_fn(part));
+                    if next_row.iter().any(|&distance| distance <= max_distance) {
+                        path.push(part);
+                        child.fuzzy(query, max_distance, index_fn, path, &next_row, found);
+                        path.pop();
+                    }
+                }
+            }
+            Node::Compressed { compressed, child, value } => {
+                if value.is_some() && within(row) {
+                    found.push(path.clone());
+                }
+                let depth = path.len();
+                let mut row = row.to_vec();
+                for part in compressed.iter() {
+                    row = edit_row(&row, query, index_fn(part));
+                    if row.iter().all(|&distance| distance > max_distance) {
+                        path.truncate(depth);
+                        return;
+                    }
+                    path.push(part);
+                }
+                child.fuzzy(query, max_distance, index_fn, path, &row, found);
+                path.truncate(depth);
+            }
+        }
+    }
+
+    /// collects every element stored below this node with exactly one part left per slot of
+    /// `pattern`, each part either matching the predicate of its slot or sitting under a `None`
+    pub(crate) fn matching<'a, F>(&'a self, pattern: &[Option<F>], path: &mut Vec<&'a T>, found: &mut Vec<Vec<&'a T>>)
+        where F: Fn(&T) -> bool {
+        let matches = |slot: &Option<F>, part: &T| slot.as_ref().is_none_or(|pred| pred(part));
+        if pattern.is_empty() {
+            if self.value().is_some() {
+                found.push(path.clone());
+            }
+            return;
+        }
+        match self {
+            Node::Empty | Node::Leaf(_) => {}
+            Node::Normal { children, .. } => {
+                for (_, part, child) in children.iter() {
+                    if matches(&pattern[0], part) {
+                        path.push(part);
+                        child.matching(&pattern[1..], path, found);
+                        path.pop();
+                    }
+                }
+            }
+            Node::Compressed { compressed, child, .. } => {
+                if compressed.len() > pattern.len() || !compressed.iter().zip(pattern).all(|(part, slot)| matches(slot, part)) {
+                    return;
+                }
+                let depth = path.len();
+                path.extend(compressed.iter());
+                child.matching(&pattern[compressed.len()..], path, found);
+                path.truncate(depth);
+            }
+        }
+    }
+
+    /// collects every element stored below this node within `bounds`, in index order
+    pub(crate) fn range<'a, FIndex>(&'a self, bounds: Bounds<'_>, index_fn: &FIndex, path: &mut Vec<&'a T>, found: &mut Vec<Vec<&'a T>>)
+        where FIndex: Fn(&T) -> usize {
+        if self.value().is_some() && bounds.includes_here() {
+            found.push(path.clone());
+        }
+        match self {
+            Node::Empty | Node::Leaf(_) => {}
+            Node::Normal { children, .. } => {
+                for (pos, part, child) in children.iter() {
+                    if let Some(bounds) = bounds.step(pos) {
+                        path.push(part);
+                        child.range(bounds, index_fn, path, found);
+                        path.pop();
+                    }
+                }
+            }
+            Node::Compressed { compressed, child, .. } => {
+                let depth = path.len();
+                let mut bounds = bounds;
+                for part in compressed.iter() {
+                    bounds = match bounds.step(index_fn(part)) {
+                        Some(bounds) => bounds,
+                        None => {
+                            path.truncate(depth);
+                            return;
+                        }
+                    };
+                    path.push(part);
+                }
+                child.range(bounds, index_fn, path, found);
+                path.truncate(depth);
+            }
+        }
+    }
+
+    /// removes every element below this node for which `f` returns false, given the parts of the
+    /// element after `path`, returns the number of elements removed
+    ///
+    /// nodes are collapsed on the way back up the same as `remove` would
+    pub(crate) fn retain<F>(&mut self, path: &mut Vec<T>, f: &mut F) -> usize
+        where T: Clone, F: FnMut(&[T], &V) -> bool {
+        match self {
+            Node::Empty => 0,
+            Node::Leaf(value) => {
+                if f(path, value) {
+                    return 0;
+                }
+                *self = Node::Empty;
+                1
+            }
+            Node::Normal { children, value } => {
+                let mut removed = 0;
+                if value.as_ref().is_some_and(|value| !f(path, value)) {
+                    *value = None;
+                    removed += 1;
+                }
+                let positions = children.iter().map(|(pos, _, _)| pos).collect::<Vec<_>>();
+                for pos in positions {
+                    if let Some((part, child)) = children.get_mut(pos) {
+                        path.push(part.clone());
+                        removed += child.retain(path, f);
+                        path.pop();
+                        if child.is_empty() {
+                            children.remove(pos);
+                        }
+                    }
+                }
+
+                let collapsed = match children.len() {
+                    0 => Node::Empty,
+                    1 => {
+                        let (part, child) = children.take_first().unwrap();
+                        Node::new_compressed(core::iter::once(part), child)
+                    }
+                    _ => return removed,
+                };
+                *self = collapsed.with_value(value.take());
+                removed
+            }
+            Node::Compressed { compressed, child, value } => {
+                let mut removed = 0;
+                if value.as_ref().is_some_and(|value| !f(path, value)) {
+                    *value = None;
+                    removed += 1;
+                }
+                let depth = path.len();
+                path.extend(compressed.iter().cloned());
+                removed += child.retain(path, f);
+                path.truncate(depth);
+
+                let child = mem::replace(&mut **child, Node::Empty);
+                let collapsed = Node::new_compressed(mem::take(compressed).into_iter(), child);
+                *self = collapsed.with_value(value.take());
+                removed
+            }
+        }
+    }
+
+    /// releases spare capacity of every node below this one, see `Trie::shrink_to_fit`
+    pub(crate) fn shrink_to_fit(&mut self) {
+        match self {
+            Node::Empty | Node::Leaf(_) => {}
+            Node::Normal { children, .. } => {
+                children.shrink_to_fit();
+                let positions = children.iter().map(|(pos, _, _)| pos).collect::<Vec<_>>();
+                for pos in positions {
+                    if let Some((_, child)) = children.get_mut(pos) {
+                        child.shrink_to_fit();
+                    }
+                }
+            }
+            Node::Compressed { compressed, child, .. } => {
+                compressed.shrink_to_fit();
+                child.shrink_to_fit();
+            }
+        }
+    }
+
+    /// bytes allocated by this node and every node below it, see `Trie::memory_usage`
+    pub(crate) fn heap_size(&self) -> usize {
+        match self {
+            Node::Empty | Node::Leaf(_) => 0,
+            Node::Normal { children, .. } => {
+                children.heap_size() + children.iter().map(|(_, _, child)| child.heap_size()).sum::<usize>()
+            }
+            Node::Compressed { compressed, child, .. } => {
+                compressed.capacity() * mem::size_of::<T>() + mem::size_of::<Node<T, V>>() + child.heap_size()
+            }
+        }
+    }
+
+    /// checks the shape of this node and every node below it, see `Trie::check_invariants`,
+    /// returns the number of terminal nodes found. `path` holds the part indices leading here
+    pub(crate) fn check_invariants<FIndex>(&self, path: &mut Vec<usize>, index_fn: &FIndex, alphabet_size: usize) -> Result<usize, String>
+        where FIndex: Fn(&T) -> usize {
+        let own = self.value().is_some() as usize;
+        match self {
+            Node::Empty | Node::Leaf(_) => Ok(own),
+            Node::Normal { children, .. } => {
+                if let Children::Dense { slots, occupied } = children {
+                    if slots.len() != alphabet_size {
+                        return Err(alloc::format!("normal node at {:?} has {} slots for an alphabet of {}", path, slots.len(), alphabet_size));
+                    }
+                    let counted = slots.iter().filter(|slot| slot.is_some()).count();
+                    if counted != *occupied {
+                        return Err(alloc::format!("normal node at {:?} counts {} occupied slots out of {}", path, occupied, counted));
+                    }
+                }
+                if children.len() == 0 && own == 0 && !path.is_empty() {
+                    return Err(alloc::format!("normal node at {:?} holds neither children nor a value", path));
+                }
+                let mut terminals = own;
+                for (pos, part, child) in children.iter() {
+                    let index = index_fn(part);
+                    if index != pos || pos >= alphabet_size {
+                        return Err(alloc::format!("child at {:?} is held at position {} of {} though its part indexes to {}", path, pos, alphabet_size, index));
+                    }
+                    path.push(pos);
+                    if child.is_empty() {
+                        return Err(alloc::format!("normal node leads to an empty node at {:?}", path));
+                    }
+                    terminals += child.check_invariants(path, index_fn, alphabet_size)?;
+                    path.pop();
+                }
+                Ok(terminals)
+            }
+            Node::Compressed { compressed, child, .. } => {
+                if compressed.is_empty() {
+                    return Err(alloc::format!("compressed run at {:?} is empty", path));
+                }
+                if let Some(index) = compressed.iter().map(index_fn).find(|index| *index >= alphabet_size) {
+                    return Err(alloc::format!("compressed run at {:?} holds a part indexing to {}, out of an alphabet of {}", path, index, alphabet_size));
+                }
+                let depth = path.len();
+                path.extend(compressed.iter().map(index_fn));
+                match &**child {
+                    Node::Empty => return Err(alloc::format!("compressed run leads to an empty node at {:?}", path)),
+                    Node::Compressed { value: None, .. } => {
+                        return Err(alloc::format!("compressed run leads to a non terminal compressed run at {:?}, rather than the two being one", path));
+                    }
+                    _ => {}
+                }
+                let terminals = own + child.check_invariants(path, index_fn, alphabet_size)?;
+                path.truncate(depth);
+                Ok(terminals)
+            }
+        }
+    }
+
+    /// the parts of the elements stored at or below this node, counted once per element, and the
+    /// parts held by this node and the nodes below it, this node being `depth` parts below the root
+    pub(crate) fn part_counts(&self, depth: usize) -> (usize, usize) {
+        let own = if self.value().is_some() { depth } else { 0 };
+        match self {
+            Node::Empty | Node::Leaf(_) => (own, 0),
+            Node::Normal { children, .. } => children.iter().fold((own, children.len()), |(total, stored), (_, _, child)| {
+                let (child_total, child_stored) = child.part_counts(depth + 1);
+                (total + child_total, stored + child_stored)
+            }),
+            Node::Compressed { compressed, child, .. } => {
+                let (total, stored) = child.part_counts(depth + compressed.len());
+                (own + total, compressed.len() + stored)
+            }
+        }
+    }
+
+    /// hands the parts of every element stored at or below this node to `f`, `parts` holding the
+    /// parts leading here, in the order of `Trie::iter`
+    pub(crate) fn for_each_key<F: FnMut(&[T])>(&self, parts: &mut Vec<T>, f: &mut F) where T: Clone {
+        if self.value().is_some() {
+            f(parts);
+        }
+        match self {
+            Node::Empty | Node::Leaf(_) => {}
+            Node::Normal { children, .. } => {
+                for (_, part, child) in children.iter() {
+                    parts.push(part.clone());
+                    child.for_each_key(parts, f);
+                    parts.pop();
+                }
+            }
+            Node::Compressed { compressed, child, .. } => {
+                let depth = parts.len();
+                parts.extend_from_slice(compressed);
+                child.for_each_key(parts, f);
+                parts.truncate(depth);
+            }
+        }
+    }
+
+    /// adds this node and every node below it to `stats`, this node being `depth` nodes below the
+    /// root
+    pub(crate) fn stats(&self, depth: usize, stats: &mut TrieStats) {
+        stats.max_depth = stats.max_depth.max(depth);
+        if self.value().is_some() {
+            stats.terminals += 1;
+        }
+        match self {
+            Node::Empty => stats.empty_nodes += 1,
+            Node::Leaf(_) => stats.leaf_nodes += 1,
+            Node::Normal { children, .. } => {
+                stats.normal_nodes += 1;
+                for (_, _, child) in children.iter() {
+                    child.stats(depth + 1, stats);
+                }
+            }
+            Node::Compressed { compressed, child, .. } => {
+                stats.compressed_nodes += 1;
+                stats.compressed_parts += compressed.len();
+                child.stats(depth + 1, stats);
+            }
+        }
+    }
+
+    /// returns the value stored at the end of the path of `positions` for mutation, the indices of
+    /// the parts of an element rather than the parts themselves
+    pub(crate) fn get_mut<TIt, FIndex>(&mut self, positions: &mut Peekable<TIt>, index_fn: &FIndex) -> Option<&mut V>
+        where TIt: Iterator<Item=usize>, FIndex: Fn(&T) -> usize {
+        match self {
+            Node::Empty => None,
+            Node::Leaf(value) => {
+                if positions.next().is_none() { Some(value) } else { None }
+            }
+            Node::Normal { children, value } => {
+                match positions.next() {
+                    Some(pos) => match children.get_mut(pos) {
+                        Some((_, child)) => child.get_mut(positions, index_fn),
+                        None => None,
+                    },
+                    None => value.as_mut(),
+                }
+            }
+            Node::Compressed { compressed, child, value } => {
+                if positions.peek().is_none() {
+                    return value.as_mut();
+                }
+                for held_part in compressed.iter() {
+                    match positions.next() {
+                        Some(pos) if index_fn(held_part) == pos => {}
+                        _ => return None,
+                    }
+                }
+                child.get_mut(positions, index_fn)
+            }
+        }
+    }
+
+    /// removes the value stored at the end of the path of `it`
+    ///
+    /// nodes freed by the removal are collapsed such that a `Normal` node left with a single child
+    /// is compressed back into its parent run
+    pub(crate) fn remove<TIt, FIndex>(&mut self, it: &mut Peekable<TIt>, index_fn: &FIndex) -> Option<V>
+        where TIt: Iterator<Item=T>, FIndex: Fn(&T) -> usize {
+        match self {
+            Node::Empty => None,
+            Node::Leaf(_) => {
+                if it.next().is_some() {
+                    return None;
+                }
+                match mem::replace(self, Node::Empty) {
+                    Node::Leaf(value) => Some(value),
+                    _ => unreachable!(),
+                }
+            }
+            Node::Normal { children, value } => {
+                let removed = match it.next() {
+                    Some(part) => {
+                        let pos = index_fn(&part);
+                        let (removed, child_emptied) = match children.get_mut(pos) {
+                            Some((_, child)) => (child.remove(it, index_fn)?, child.is_empty()),
+                            None => return None,
+                        };
+                        if child_emptied {
+                            children.remove(pos);
+                        }
+                        removed
+                    }
+                    None => value.take()?,
+                };
+
+                let collapsed = match children.len() {
+                    0 => Node::Empty,
+                    1 => {
+                        let (part, child) = children.take_first().unwrap();
+                        Node::new_compressed(core::iter::once(part), child)
+                    }
+                    _ => return Some(removed),
+                };
+                *self = collapsed.with_value(value.take());
+                Some(removed)
+            }
+            Node::Compressed { compressed, child, value } => {
+                let removed = if it.peek().is_none() {
+                    value.take()?
+                } else {
+                    for held_part in compressed.iter() {
+                        match it.next() {
+                            Some(part) if index_fn(held_part) == index_fn(&part) => {}
+                            _ => return None,
+                        }
+                    }
+                    child.remove(it, index_fn)?
+                };
+
+                let child = mem::replace(&mut **child, Node::Empty);
+                let collapsed = Node::new_compressed(mem::take(compressed).into_iter(), child);
+                *self = collapsed.with_value(value.take());
+                Some(removed)
+            }
+        }
+    }
+
+    /// detaches everything stored at or below the end of the path of `it`, returning the node at
+    /// that position
+    ///
+    /// a path ending inside a `Compressed` run takes the rest of the run along with it. what is left
+    /// is collapsed as `remove` does
+    pub(crate) fn take_prefix<Q, TIt, FIndex>(&mut self, it: &mut Peekable<TIt>, index_fn: &FIndex) -> Option<Node<T, V>>
+        where Q: Borrow<T>, TIt: Iterator<Item=Q>, FIndex: Fn(&T) -> usize {
+        if self.is_empty() {
+            return None;
+        }
+        if it.peek().is_none() {
+            return Some(mem::replace(self, Node::Empty));
+        }
+        match self {
+            Node::Empty | Node::Leaf(_) => None,
+            Node::Normal { children, value } => {
+                let pos = index_fn(it.next().unwrap().borrow());
+                let (taken, child_emptied) = match children.get_mut(pos) {
+                    Some((_, child)) => (child.take_prefix(it, index_fn)?, child.is_empty()),
+                    None => return None,
+                };
+                if child_emptied {
+                    children.remove(pos);
+                }
+
+                let collapsed = match children.len() {
+                    0 => Node::Empty,
+                    1 => {
+                        let (part, child) = children.take_first().unwrap();
+                        Node::new_compressed(core::iter::once(part), child)
+                    }
+                    _ => return Some(taken),
+                };
+                *self = collapsed.with_value(value.take());
+                Some(taken)
+            }
+            Node::Compressed { compressed, child, value } => {
+                let mut ends_at = None;
+                for (i, held_part) in compressed.iter().enumerate() {
+                    match it.next() {
+                        Some(part) if index_fn(held_part) == index_fn(part.borrow()) => {}
+                        Some(_) => return None,
+                        None => {
+                            ends_at = Some(i);
+                            break;
+                        }
+                    }
+                }
+
+                let taken = match ends_at {
+                    Some(i) => {
+                        let rest = compressed.split_off(i);
+                        let child = mem::replace(&mut **child, Node::Empty);
+                        let taken = Node::new_compressed(rest.into_iter(), child);
+                        *self = Node::Empty.with_value(value.take());
+                        return Some(taken);
+                    }
+                    None => child.take_prefix(it, index_fn)?,
+                };
+
+                let child = mem::replace(&mut **child, Node::Empty);
+                let collapsed = Node::new_compressed(mem::take(compressed).into_iter(), child);
+                *self = collapsed.with_value(value.take());
+                Some(taken)
+            }
+        }
+    }
+
+    /// moves the elements of `other` into this node, handing the values of elements held by both to
+    /// `merge_value` along with the value of `other`
+    ///
+    /// subtrees of `other` at positions this node does not hold are moved over whole, nodes are only
+    /// taken apart where both hold elements below them
+    pub(crate) fn merge<FMerge, FIndex>(&mut self, other: Node<T, V>, merge_value: &mut FMerge, index_fn: &FIndex, alphabet_size: usize)
+        where FMerge: FnMut(&mut V, V), FIndex: Fn(&T) -> usize {
+        if other.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            *self = other;
+            return;
+        }
+        let merge_values = |value: &mut Option<V>, other_value: Option<V>, merge_value: &mut FMerge| match (value.as_mut(), other_value) {
+            (Some(value), Some(other_value)) => merge_value(value, other_value),
+            (None, other_value) => *value = other_value,
+            (Some(_), None) => {}
+        };
+
+        if let Node::Normal { children, value } = self {
+            let (other_value, other_steps) = other.into_steps(index_fn);
+            merge_values(value, other_value, merge_value);
+            for (pos, part, other_child) in other_steps {
+                match children.get_mut(pos) {
+                    Some((_, child)) => child.merge(other_child, merge_value, index_fn, alphabet_size),
+                    None => children.insert(pos, part, other_child),
+                }
+            }
+            return;
+        }
+
+        // a leaf or a run, which has at most one step to pair with those of `other`
+        let (mut value, mut steps) = mem::replace(self, Node::Empty).into_steps(index_fn);
+        let (other_value, other_steps) = other.into_steps(index_fn);
+        merge_values(&mut value, other_value, merge_value);
+        for (pos, part, other_child) in other_steps {
+            match steps.iter_mut().find(|(step_pos, _, _)| *step_pos == pos) {
+                Some((_, _, child)) => child.merge(other_child, merge_value, index_fn, alphabet_size),
+                None => steps.push((pos, part, other_child)),
+            }
+        }
+        *self = match steps.len() {
+            0 => Node::Empty,
+            1 => {
+                let (_, part, child) = steps.pop().unwrap();
+                Node::new_compressed(core::iter::once(part), child)
+            }
+            _ => Node::new_normal(steps, alphabet_size),
+        }.with_value(value);
+    }
+
+    /// the value of the node along with the positions one part below it, each with its part and
+    /// node. a `Compressed` node steps into the rest of its run, shrunk to fit as every run is
+    fn into_steps<FIndex: Fn(&T) -> usize>(self, index_fn: &FIndex) -> (Option<V>, Owned<T, V>) {
+        match self {
+            Node::Empty => (None, Vec::new()),
+            Node::Leaf(value) => (Some(value), Vec::new()),
+            Node::Normal { children, value } => {
+                (value, children.into_vec().into_iter().map(|(part, child)| (index_fn(&part), part, child)).collect())
+            }
+            Node::Compressed { mut compressed, child, value } => {
+                let part = compressed.remove(0);
+                compressed.shrink_to_fit();
+                let rest = if compressed.is_empty() { *child } else { Node::Compressed { compressed, child, value: None } };
+                (value, vec![(index_fn(&part), part, rest)])
+            }
+        }
+    }
+}
+
+/// the positions one part below a node taken apart, each with its part and node
+type Owned<T, V> = Vec<(usize, T, Node<T, V>)>;
+
+/// shallow description of a node, children are listed by their occupied positions only
+impl<T: fmt::Debug, V> fmt::Debug for Node<T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Node::Empty => write!(f, "Empty"),
+            Node::Leaf(_) => write!(f, "Leaf"),
+            Node::Normal { children, value } => f.debug_struct("Normal")
+                .field("children", &children.iter().map(|(pos, _, _)| pos).collect::<Vec<_>>())
+                .field("terminal", &value.is_some())
+                .finish(),
+            Node::Compressed { compressed, value, .. } => f.debug_struct("Compressed")
+                .field("compressed", compressed)
+                .field("terminal", &value.is_some())
+                .finish(),
+        }
+    }
+}
+
+/// indented rendering of a whole subtree, one node per line with every child below its parent
+pub(crate) struct TreeDisplay<'a, T, V> {
+    root: &'a Node<T, V>,
+}
+
+impl<'a, T: fmt::Debug, V> TreeDisplay<'a, T, V> {
+    fn print_me(node: &Node<T, V>, indent: usize, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:indent$}{:?}", "", node, indent = indent)?;
+        match node {
+            Node::Normal { children, .. } => {
+                for (pos, part, child) in children.iter() {
+                    writeln!(f, "{:indent$}{} {:?} ->", "", pos, part, indent = indent + 2)?;
+                    TreeDisplay::print_me(child, indent + 4, f)?;
+                }
+            }
+            Node::Compressed { child, .. } => {
+                TreeDisplay::print_me(child, indent + 2, f)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T: fmt::Debug, V> fmt::Display for TreeDisplay<'a, T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        TreeDisplay::print_me(self.root, 0, f)
+    }
+}
+
+/// writes the vertex of `node` and those of its subtree along with the edges between them in DOT,
+/// returns the id of the vertex of `node`, the first of `next_id` on
+fn write_dot<T: fmt::Debug, V>(node: &Node<T, V>, next_id: &mut usize, out: &mut String) -> Result<usize, fmt::Error> {
+    let id = *next_id;
+    *next_id += 1;
+    let kind = match node {
+        Node::Empty => "Empty",
+        Node::Leaf(_) => "Leaf",
+        Node::Normal { .. } => "Normal",
+        Node::Compressed { .. } => "Compressed",
+    };
+    let shape = if node.value().is_some() { "doublecircle" } else { "circle" };
+    writeln!(out, "    n{} [label=\"{}\", shape={}];", id, kind, shape)?;
+    match node {
+        Node::Normal { children, .. } => {
+            for (pos, _, child) in children.iter() {
+                let child_id = write_dot(child, next_id, out)?;
+                writeln!(out, "    n{} -> n{} [label=\"{}\"];", id, child_id, pos)?;
+            }
+        }
+        Node::Compressed { compressed, child, .. } => {
+            let child_id = write_dot(child, next_id, out)?;
+            let run = alloc::format!("{:?}", compressed).replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(out, "    n{} -> n{} [label=\"{}\"];", id, child_id, run)?;
+        }
+        Node::Empty | Node::Leaf(_) => {}
+    }
+    Ok(id)
+}
+
+/// wraps `alphabet` such that an index outside of the alphabet panics with a descriptive message
+/// rather than an opaque out of bounds access
+pub(crate) fn checked_index<'a, T, A: Alphabet<T>>(alphabet: &'a A, alphabet_size: usize) -> impl Fn(&T) -> usize + 'a {
+    move |part| {
+        let pos = alphabet.index(part);
+        assert!(pos < alphabet_size, "index {} is out of range for alphabet_size {}", pos, alphabet_size);
+        pos
+    }
+}
+
+/// the parts of `it` not skipped by `alphabet`, see `Alphabet::skips`
+pub(crate) fn kept<T, Q, TIt, A>(it: TIt, alphabet: &A) -> Kept<'_, T, TIt, A>
+    where Q: Borrow<T>, TIt: Iterator<Item=Q>, A: Alphabet<T> {
+    Kept { it, alphabet, part: PhantomData }
+}
+
+/// the parts of an iterator not skipped by an alphabet, see `kept`
+///
+/// parts are handed on as they are for alphabets which skip none, along with the size hint of the
+/// iterator, such that runs can still be allocated to fit up front
+pub(crate) struct Kept<'a, T, TIt, A> {
+    it: TIt,
+    alphabet: &'a A,
+    part: PhantomData<fn(&T)>,
+}
+
+impl<'a, T, Q: Borrow<T>, TIt: Iterator<Item=Q>, A: Alphabet<T>> Iterator for Kept<'a, T, TIt, A> {
+    type Item = Q;
+
+    fn next(&mut self) -> Option<Q> {
+        if !A::SKIPS {
+            return self.it.next();
+        }
+        let alphabet = self.alphabet;
+        self.it.find(|part| !alphabet.skips(part.borrow()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.it.size_hint();
+        (if A::SKIPS { 0 } else { lower }, upper)
+    }
+}
+
+/// decomposes up front, failing if any part's index is outside of the alphabet
+pub(crate) fn validated<T, TIt: Iterator<Item=T>, A: Alphabet<T>>(it: TIt, alphabet: &A, alphabet_size: usize) -> Result<Vec<T>, TrieError> {
+    kept(it, alphabet).map(|part| {
+        let got = alphabet.index(&part);
+        if got < alphabet_size { Ok(part) } else { Err(TrieError::IndexOutOfRange { got, alphabet_size }) }
+    }).collect()
+}
+
+/// the levenshtein distances of a path extended by a part of index `pos`, given the distances `row`
+/// of the path to every prefix of `query`
+fn edit_row(row: &[usize], query: &[usize], pos: usize) -> Vec<usize> {
+    let mut next_row = Vec::with_capacity(row.len());
+    next_row.push(row[0] + 1);
+    for (i, &query_pos) in query.iter().enumerate() {
+        let substitution = row[i] + if query_pos == pos { 0 } else { 1 };
+        let distance = substitution.min(row[i + 1] + 1).min(next_row[i] + 1);
+        next_row.push(distance);
+    }
+    next_row
+}
+
+/// the half open range `lo..hi` of a range walk, as the indices of the bound parts left to compare
+/// against the parts below the current node
+///
+/// a bound is dropped once a part differs from it, from then on the whole subtree is on the inner
+/// side of it
+#[derive(Clone, Copy)]
+pub(crate) struct Bounds<'b> {
+    pub(crate) lo: Option<&'b [usize]>,
+    pub(crate) hi: Option<&'b [usize]>,
+}
+
+impl<'b> Bounds<'b> {
+    /// narrows the bounds past a part of index `pos`, `None` if the subtree below it is out of range
+    fn step(self, pos: usize) -> Option<Bounds<'b>> {
+        let lo = match self.lo {
+            Some([bound, rest @ ..]) if pos == *bound => Some(rest),
+            Some([bound, ..]) if pos < *bound => return None,
+            _ => None,
+        };
+        let hi = match self.hi {
+            Some([bound, rest @ ..]) if pos == *bound => Some(rest),
+            Some([bound, ..]) if pos < *bound => None,
+            Some(_) => return None,
+            None => None,
+        };
+        Some(Bounds { lo, hi })
+    }
+
+    /// whether an element ending at the current node is in range, a proper prefix of `lo` is below
+    /// it while `hi` itself is excluded
+    fn includes_here(&self) -> bool {
+        self.lo.is_none_or(<[usize]>::is_empty) && self.hi.is_none_or(|hi| !hi.is_empty())
+    }
+}
+
+/// a position in a node tree during a simultaneous walk of two trees, `Run` being partway into a
+/// `Compressed` run with the rest of its parts still to go before its child
+enum Position<'a, T, V> {
+    Node(&'a Node<T, V>),
+    Run(&'a [T], &'a Node<T, V>),
+}
+
+impl<'a, T, V> Clone for Position<'a, T, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T, V> Copy for Position<'a, T, V> {}
+
+/// the positions one part past a position, along with the part leading to each
+type Steps<'a, T, V> = Vec<(usize, &'a T, Position<'a, T, V>)>;
+
+impl<'a, T, V> Position<'a, T, V> {
+    /// the value of the element ending at the position, along with the positions one part further
+    fn expand<FIndex: Fn(&T) -> usize>(self, index_fn: &FIndex) -> (Option<&'a V>, Steps<'a, T, V>) {
+        match self {
+            Position::Node(node @ Node::Empty) | Position::Node(node @ Node::Leaf(_)) => (node.value(), Vec::new()),
+            Position::Node(Node::Normal { children, value }) => {
+                (value.as_ref(), children.iter().map(|(pos, part, child)| (pos, part, Position::Node(child))).collect())
+            }
+            Position::Node(Node::Compressed { compressed, child, value }) => {
+                (value.as_ref(), vec![(index_fn(&compressed[0]), &compressed[0], Position::Run(&compressed[1..], child))])
+            }
+            Position::Run([], child) => Position::Node(child).expand(index_fn),
+            Position::Run([part, rest @ ..], child) => (None, vec![(index_fn(part), part, Position::Run(rest, child))]),
+        }
+    }
+
+    /// the position one part further at `pos` along with the stored part leading to it, an
+    /// exhausted run continuing at its child
+    fn step<FIndex: Fn(&T) -> usize>(self, pos: usize, index_fn: &FIndex) -> Option<(&'a T, Position<'a, T, V>)> {
+        let (part, rest, child) = match self {
+            Position::Node(Node::Empty) | Position::Node(Node::Leaf(_)) => return None,
+            Position::Node(Node::Normal { children, .. }) => return children.get(pos).map(|(part, child)| (part, Position::Node(child))),
+            Position::Node(Node::Compressed { compressed, child, .. }) => (&compressed[0], &compressed[1..], &**child),
+            Position::Run([], child) => return Position::Node(child).step(pos, index_fn),
+            Position::Run([part, rest @ ..], child) => (part, rest, child),
+        };
+        if index_fn(part) != pos {
+            return None;
+        }
+        Some((part, if rest.is_empty() { Position::Node(child) } else { Position::Run(rest, child) }))
+    }
+
+    /// the value of the element ending at this position
+    fn value(self) -> Option<&'a V> {
+        match self {
+            Position::Node(node) | Position::Run([], node) => node.value(),
+            Position::Run(..) => None,
+        }
+    }
+
+    /// the stored parts of the least element below this position, see `Node::first`
+    fn first(self) -> Option<Vec<&'a T>> {
+        match self {
+            Position::Node(node) => node.first(),
+            Position::Run(rest, child) => child.first().map(|tail| rest.iter().chain(tail).collect()),
+        }
+    }
+
+    /// the stored parts of the greatest element below this position, see `Node::last`
+    fn last(self) -> Option<Vec<&'a T>> {
+        match self {
+            Position::Node(node) => node.last(),
+            Position::Run(rest, child) => child.last().map(|tail| rest.iter().chain(tail).collect()),
+        }
+    }
+
+    /// number of positions one part further
+    fn len(self) -> usize {
+        match self {
+            Position::Node(Node::Empty) | Position::Node(Node::Leaf(_)) => 0,
+            Position::Node(Node::Normal { children, .. }) => children.len(),
+            Position::Run([], child) => Position::Node(child).len(),
+            Position::Node(Node::Compressed { .. }) | Position::Run(..) => 1,
+        }
+    }
+}
+
+/// whether the trees below both positions hold elements at the same positions, whatever their shape
+fn same_elements<T, V, FIndex: Fn(&T) -> usize>(a: Position<T, V>, b: Position<T, V>, index_fn: &FIndex, other_index_fn: &FIndex) -> bool {
+    let (a_value, a_children) = a.expand(index_fn);
+    let (b_value, b_children) = b.expand(other_index_fn);
+    a_value.is_some() == b_value.is_some()
+        && a_children.len() == b_children.len()
+        && a_children.into_iter().zip(b_children).all(|((a_pos, _, a), (b_pos, _, b))| {
+            a_pos == b_pos && same_elements(a, b, index_fn, other_index_fn)
+        })
+}
+
+/// collects every element below `a` which is not held below `b`, in index order, given the parts
+/// leading to `a` in `path`
+///
+/// both trees are walked at once part by part as with `combine`, the walk of `b` stopping where it
+/// holds nothing further
+fn added<'a, T, V, FIndex>(a: Position<'a, T, V>, b: Option<Position<'_, T, V>>, index_fn: &FIndex, path: &mut Vec<&'a T>, found: &mut Vec<Vec<&'a T>>)
+    where FIndex: Fn(&T) -> usize {
+    let (a_value, a_children) = a.expand(index_fn);
+    let (b_value, b_children) = b.map_or((None, Vec::new()), |b| b.expand(index_fn));
+    if a_value.is_some() && b_value.is_none() {
+        found.push(path.clone());
+    }
+
+    // children of both are listed by ascending position
+    let mut b_children = b_children.into_iter().peekable();
+    for (pos, part, a) in a_children {
+        while b_children.next_if(|&(b_pos, _, _)| b_pos < pos).is_some() {}
+        let b = b_children.next_if(|&(b_pos, _, _)| b_pos == pos).map(|(_, _, b)| b);
+        path.push(part);
+        added(a, b, index_fn, path, found);
+        path.pop();
+    }
+}
+
+/// the stored parts of the least element below `position` greater than `query`, given as the
+/// indices of its parts left past `path`
+///
+/// children are tried by ascending index, the one on the path of `query` first looked into for a
+/// greater element, any child past it holding only greater elements
+fn successor<'a, T, V, FIndex>(position: Position<'a, T, V>, query: &[usize], index_fn: &FIndex, path: &mut Vec<&'a T>) -> Option<Vec<&'a T>>
+    where FIndex: Fn(&T) -> usize {
+    let (_, steps) = position.expand(index_fn);
+    for (pos, part, child) in steps {
+        path.push(part);
+        let found = match query.split_first() {
+            Some((&query_pos, rest)) if pos == query_pos => successor(child, rest, index_fn, path),
+            Some((&query_pos, _)) if pos < query_pos => None,
+            _ => child.first().map(|tail| path.iter().copied().chain(tail).collect()),
+        };
+        path.pop();
+        if found.is_some() {
+            return found;
+        }
+    }
+    None
+}
+
+/// the stored parts of the greatest element below `position` less than `query`, see `successor`
+///
+/// an element ending at `position` is a prefix of `query`, coming before it but after nothing
+/// else below
+fn predecessor<'a, T, V, FIndex>(position: Position<'a, T, V>, query: &[usize], index_fn: &FIndex, path: &mut Vec<&'a T>) -> Option<Vec<&'a T>>
+    where FIndex: Fn(&T) -> usize {
+    let (&query_pos, rest) = query.split_first()?;
+    let (value, steps) = position.expand(index_fn);
+    for (pos, part, child) in steps.into_iter().rev() {
+        if pos > query_pos {
+            continue;
+        }
+        path.push(part);
+        let found = if pos == query_pos {
+            predecessor(child, rest, index_fn, path)
+        } else {
+            child.last().map(|tail| path.iter().copied().chain(tail).collect())
+        };
+        path.pop();
+        if found.is_some() {
+            return found;
+        }
+    }
+    value.map(|_| path.clone())
+}
+
+/// builds the tree of the elements of `a` and `b` for which `keep` holds given their membership in
+/// either tree, the values being those of `a` where it holds the element
+///
+/// both trees are walked at once part by part, such that a `Compressed` run of one side lines up
+/// with the `Normal` nodes of the other. subtrees that can hold no kept element are skipped
+fn combine<T: Clone, V: Clone, FIndex, FKeep>(a: Option<Position<T, V>>, b: Option<Position<T, V>>, keep: &FKeep, index_fn: &FIndex, alphabet_size: usize) -> Node<T, V>
+    where FIndex: Fn(&T) -> usize, FKeep: Fn(bool, bool) -> bool {
+    let (a_value, a_children) = a.map_or((None, Vec::new()), |a| a.expand(index_fn));
+    let (b_value, b_children) = b.map_or((None, Vec::new()), |b| b.expand(index_fn));
+    let value = if keep(a_value.is_some(), b_value.is_some()) { a_value.or(b_value).cloned() } else { None };
+
+    let mut paired = BTreeMap::new();
+    for (pos, part, cursor) in a_children {
+        paired.insert(pos, (part, Some(cursor), None));
+    }
+    for (pos, part, cursor) in b_children {
+        paired.entry(pos).or_insert((part, None, None)).2 = Some(cursor);
+    }
+    let mut children = Vec::new();
+    for (pos, (part, a, b)) in paired {
+        if (b.is_none() && !keep(true, false)) || (a.is_none() && !keep(false, true)) {
+            continue;
+        }
+        let child = combine(a, b, keep, index_fn, alphabet_size);
+        if !child.is_empty() {
+            children.push((pos, part.clone(), child));
+        }
+    }
+
+    match children.len() {
+        0 => Node::Empty.with_value(value),
+        1 => {
+            let (_, part, child) = children.pop().unwrap();
+            Node::new_compressed(core::iter::once(part), child).with_value(value)
+        }
+        _ => Node::new_normal(children, alphabet_size).with_value(value),
+    }
+}
+
+/// hands a stored value to `update`, or stores `new_value` if there is none
+fn update_value<V, FUpdate: FnOnce(&mut V, V) -> V>(value: &mut Option<V>, new_value: V, update: FUpdate) -> Option<V> {
+    match value {
+        Some(value) => Some(update(value, new_value)),
+        None => {
+            *value = Some(new_value);
+            None
+        }
+    }
+}
+
+/// a node on the path of the last element given to `build_sorted`, still open to children
+///
+/// `run` holds the parts leading to the node from its parent, the first of which is at `pos` of the
+/// parent, and `depth` the number of parts leading to it from the root. the children of the node
+/// are the closed ones from `first` on
+struct Open<T> {
+    depth: usize,
+    pos: usize,
+    run: Vec<T>,
+    value: Option<usize>,
+    first: usize,
+}
+
+/// the closed children of every open node, those of deeper nodes after those of their parents
+type Closed<T> = Vec<(usize, T, Node<T, usize>)>;
+
+impl<T> Open<T> {
+    /// the node at the end of the run, taking its children out of `closed`
+    fn build(first: usize, value: Option<usize>, closed: &mut Closed<T>, alphabet_size: usize) -> Node<T, usize> {
+        match closed.len() - first {
+            0 => Node::Empty.with_value(value),
+            1 => {
+                let (_, part, child) = closed.pop().unwrap();
+                Node::new_compressed(core::iter::once(part), child).with_value(value)
+            }
+            _ => Node::new_normal(closed.drain(first..), alphabet_size).with_value(value),
+        }
+    }
+
+    /// builds the node along with the run leading to it, adding it to the children of its parent
+    fn close(self, closed: &mut Closed<T>, alphabet_size: usize) {
+        let node = Open::build(self.first, self.value, closed, alphabet_size);
+        let mut run = self.run.into_iter();
+        let part = run.next().unwrap();
+        closed.push((self.pos, part, Node::new_compressed(run, node)));
+    }
+}
+
+/// builds the tree of `elements`, sorted by the indices of their parts, returning it along with
+/// the number of distinct elements
+///
+/// the path of the last element is held as a stack of open nodes, one for every branch or element
+/// along it. the next element shares a prefix with the last, sorting guaranteeing that nothing is
+/// stored below the rest of the path anymore, so those nodes are closed in their final shape. the
+/// rest of the new element is opened as a single run, split once a later element leaves it midway
+fn build_sorted<T, TIt, FIndex>(elements: impl Iterator<Item=TIt>, index_fn: &FIndex, alphabet_size: usize) -> (Node<T, usize>, usize)
+    where TIt: Iterator<Item=T>, FIndex: Fn(&T) -> usize {
+    let mut open = vec![Open { depth: 0, pos: 0, run: Vec::new(), value: None, first: 0 }];
+    let mut closed = Vec::new();
+    // the indices of the parts of the last element
+    let mut last = Vec::new();
+    let mut count = 0;
+    for parts in elements {
+        let mut parts = parts.map(|part| (index_fn(&part), part));
+        let mut shared = 0;
+        let mut next = parts.next();
+        while let Some((pos, _)) = &next {
+            if last.get(shared) != Some(pos) {
+                break;
+            }
+            shared += 1;
+            next = parts.next();
+        }
+        assert!(
+            match &next {
+                Some((pos, _)) => last.get(shared).is_none_or(|last| last < pos),
+                None => shared == last.len(),
+            },
+            "from_sorted_iter was given unsorted elements",
+        );
+
+        while open.last().unwrap().depth > shared {
+            let mut closing = open.pop().unwrap();
+            let parent_depth = open.last().unwrap().depth;
+            if parent_depth < shared {
+                // the new element leaves the run midway, which is split by a node where it does
+                let tail = closing.run.split_off(shared - parent_depth);
+                let head = mem::replace(&mut closing.run, tail);
+                let pos = mem::replace(&mut closing.pos, last[shared]);
+                let first = closing.first;
+                closing.close(&mut closed, alphabet_size);
+                open.push(Open { depth: shared, pos, run: head, value: None, first });
+            } else {
+                closing.close(&mut closed, alphabet_size);
+            }
+        }
+
+        last.truncate(shared);
+        match next {
+            Some((pos, part)) => {
+                let mut run = Vec::with_capacity(parts.size_hint().0 + 1);
+                run.push(part);
+                last.push(pos);
+                for (pos, part) in parts {
+                    last.push(pos);
+                    run.push(part);
+                }
+                open.push(Open { depth: last.len(), pos, run, value: Some(1), first: closed.len() });
+                count += 1;
+            }
+            None => {
+                let value = &mut open.last_mut().unwrap().value;
+                if value.is_none() {
+                    count += 1;
+                }
+                *value = Some(value.map_or(1, |frequency| frequency + 1));
+            }
+        }
+    }
+
+    while open.len() > 1 {
+        open.pop().unwrap().close(&mut closed, alphabet_size);
+    }
+    let root = open.pop().unwrap();
+    (Open::build(root.first, root.value, &mut closed, alphabet_size), count)
+}
+
+/// depth first traversal over the elements stored below a node, in index order
+pub(crate) struct Iter<'a, T, V> {
+    stack: Vec<(Vec<T>, &'a Node<T, V>)>,
+}
+
+impl<'a, T: Clone, V> Iter<'a, T, V> {
+    pub(crate) fn new(prefix: Vec<T>, node: &'a Node<T, V>) -> Iter<'a, T, V> {
+        Iter { stack: vec![(prefix, node)] }
+    }
+}
+
+impl<'a, T: Clone, V> Iterator for Iter<'a, T, V> {
+    type Item = (Vec<T>, &'a V);
+
+    fn next(&mut self) -> Option<(Vec<T>, &'a V)> {
+        while let Some((mut prefix, node)) = self.stack.pop() {
+            match node {
+                Node::Empty => {}
+                Node::Leaf(value) => return Some((prefix, value)),
+                Node::Normal { children, value } => {
+                    for (_, part, child) in children.iter().rev() {
+                        let mut child_prefix = prefix.clone();
+                        child_prefix.push(part.clone());
+                        self.stack.push((child_prefix, child));
+                    }
+                    if let Some(value) = value {
+                        return Some((prefix, value));
+                    }
+                }
+                Node::Compressed { compressed, child, value } => {
+                    if let Some(value) = value {
+                        let mut child_prefix = prefix.clone();
+                        child_prefix.extend(compressed.iter().cloned());
+                        self.stack.push((child_prefix, child));
+                        return Some((prefix, value));
+                    }
+                    prefix.extend(compressed.iter().cloned());
+                    self.stack.push((prefix, child));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// depth first traversal yielding the elements of a `Trie` front coded, see `Trie::front_coded_iter`
+pub(crate) struct FrontCodedIter<'a, T, V> {
+    /// every pending node along with its path and the length of the path of the node it was
+    /// reached from, which is what everything below it shares with what was yielded before it
+    stack: Vec<(Vec<T>, &'a Node<T, V>, usize)>,
+}
+
+impl<'a, T: Clone, V> FrontCodedIter<'a, T, V> {
+    pub(crate) fn new(node: &'a Node<T, V>) -> FrontCodedIter<'a, T, V> {
+        FrontCodedIter { stack: vec![(Vec::new(), node, 0)] }
+    }
+}
+
+impl<'a, T: Clone, V> Iterator for FrontCodedIter<'a, T, V> {
+    type Item = (usize, Vec<T>);
+
+    fn next(&mut self) -> Option<(usize, Vec<T>)> {
+        // the shallowest branch passed since the last yielded element
+        let mut shared = usize::MAX;
+        while let Some((mut prefix, node, branch)) = self.stack.pop() {
+            shared = shared.min(branch);
+            match node {
+                Node::Empty => {}
+                Node::Leaf(_) => return Some((shared, prefix.split_off(shared))),
+                Node::Normal { children, value } => {
+                    for (_, part, child) in children.iter().rev() {
+                        let mut child_prefix = prefix.clone();
+                        child_prefix.push(part.clone());
+                        self.stack.push((child_prefix, child, prefix.len()));
+                    }
+                    if value.is_some() {
+                        return Some((shared, prefix.split_off(shared)));
+                    }
+                }
+                Node::Compressed { compressed, child, value } => {
+                    if value.is_some() {
+                        let mut child_prefix = prefix.clone();
+                        child_prefix.extend(compressed.iter().cloned());
+                        self.stack.push((child_prefix, child, prefix.len()));
+                        return Some((shared, prefix.split_off(shared)));
+                    }
+                    prefix.extend(compressed.iter().cloned());
+                    self.stack.push((prefix, child, shared));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// depth first traversal of the paths of a `Trie` cut at `max_depth` parts, see `Trie::iter_to_depth`
+pub(crate) struct DepthIter<'a, T, V> {
+    stack: Vec<(Vec<T>, &'a Node<T, V>)>,
+    max_depth: usize,
+}
+
+impl<'a, T: Clone, V> DepthIter<'a, T, V> {
+    pub(crate) fn new(node: &'a Node<T, V>, max_depth: usize) -> DepthIter<'a, T, V> {
+        DepthIter { stack: vec![(Vec::new(), node)], max_depth }
+    }
+}
+
+impl<'a, T: Clone, V> Iterator for DepthIter<'a, T, V> {
+    type Item = (Vec<T>, bool);
+
+    fn next(&mut self) -> Option<(Vec<T>, bool)> {
+        while let Some((prefix, node)) = self.stack.pop() {
+            if prefix.len() == self.max_depth {
+                let below = match node {
+                    Node::Empty => continue,
+                    Node::Leaf(_) => false,
+                    Node::Normal { children, value } => {
+                        let below = children.iter().next().is_some();
+                        if !below && value.is_none() {
+                            continue;
+                        }
+                        below
+                    }
+                    Node::Compressed { .. } => true,
+                };
+                return Some((prefix, below));
+            }
+            match node {
+                Node::Empty => {}
+                Node::Leaf(_) => return Some((prefix, false)),
+                Node::Normal { children, value } => {
+                    for (_, part, child) in children.iter().rev() {
+                        let mut child_prefix = prefix.clone();
+                        child_prefix.push(part.clone());
+                        self.stack.push((child_prefix, child));
+                    }
+                    if value.is_some() {
+                        return Some((prefix, false));
+                    }
+                }
+                Node::Compressed { compressed, child, value } => {
+                    let room = self.max_depth - prefix.len();
+                    let mut child_prefix = prefix.clone();
+                    child_prefix.extend(compressed.iter().take(room).cloned());
+                    if compressed.len() > room {
+                        // the run spans the limit, the node is revisited at the limit as cut
+                        if value.is_none() {
+                            return Some((child_prefix, true));
+                        }
+                        self.stack.push((child_prefix, node));
+                    } else {
+                        self.stack.push((child_prefix, child));
+                    }
+                    if value.is_some() {
+                        return Some((prefix, false));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// a node of a `Trie` borrowed read-only, see `Trie::root_view`
+///
+/// `frequency` is the number of times the element ending at the node was inserted, 0 where no
+/// element ends
+pub enum NodeView<'a, TParts> {
+    /// the root of a trie without elements
+    Empty,
+    /// an element ending with nothing below it
+    Leaf { frequency: usize },
+    /// branches into a child per distinct next part
+    Branch { frequency: usize, children: ChildViews<'a, TParts> },
+    /// a run of parts shared by every element below it, leading to `child`
+    Run { frequency: usize, parts: &'a [TParts], child: Box<NodeView<'a, TParts>> },
+}
+
+impl<'a, TParts> NodeView<'a, TParts> {
+    fn new(node: &'a Node<TParts, usize>) -> NodeView<'a, TParts> {
+        let frequency = node.value().copied().unwrap_or(0);
+        match node {
+            Node::Empty => NodeView::Empty,
+            Node::Leaf(_) => NodeView::Leaf { frequency },
+            Node::Normal { children, .. } => NodeView::Branch { frequency, children: ChildViews { children: children.iter() } },
+            Node::Compressed { compressed, child, .. } => NodeView::Run { frequency, parts: compressed, child: Box::new(NodeView::new(child)) },
+        }
+    }
+}
+
+/// the children of a `NodeView::Branch` by ascending index, each along with its index and part
+pub struct ChildViews<'a, TParts> {
+    children: children::Iter<'a, TParts, usize>,
+}
+
+impl<'a, TParts> Iterator for ChildViews<'a, TParts> {
+    type Item = (usize, &'a TParts, NodeView<'a, TParts>);
+
+    fn next(&mut self) -> Option<(usize, &'a TParts, NodeView<'a, TParts>)> {
+        self.children.next().map(|(pos, part, child)| (pos, part, NodeView::new(child)))
+    }
+}
+
+/// counts of the nodes making up a `Trie`, returned by `Trie::stats`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrieStats {
+    pub empty_nodes: usize,
+    pub leaf_nodes: usize,
+    pub normal_nodes: usize,
+    pub compressed_nodes: usize,
+    /// parts held by the runs of all `Compressed` nodes together
+    pub compressed_parts: usize,
+    /// the most nodes below the root on any path, 0 for a trie of only its root
+    pub max_depth: usize,
+    /// nodes at which an element ends, the number of elements in the trie
+    pub terminals: usize,
+}
+
+/// a position in a `Trie` reached by following parts from the root, see `Trie::cursor`
+///
+/// a position inside a `Compressed` run is a cursor the same as one at a node, the run being
+/// followed part by part
+pub struct Cursor<'a, TParts, A: Alphabet<TParts>> {
+    position: Position<'a, TParts, usize>,
+    alphabet: &'a A,
+    alphabet_size: usize,
+}
+
+impl<'a, TParts, A: Alphabet<TParts>> Clone for Cursor<'a, TParts, A> {
+    fn clone(&self) -> Self {
+        Cursor { position: self.position, alphabet: self.alphabet, alphabet_size: self.alphabet_size }
+    }
+}
+
+impl<'a, TParts, A: Alphabet<TParts>> Cursor<'a, TParts, A> {
+    /// the cursor one `part` further, `None` if no stored element continues with it
+    ///
+    /// a part the alphabet skips leaves the cursor where it is
+    pub fn child(&self, part: &TParts) -> Option<Cursor<'a, TParts, A>> {
+        if A::SKIPS && self.alphabet.skips(part) {
+            return Some(self.clone());
+        }
+        let index_fn = checked_index(self.alphabet, self.alphabet_size);
+        let (_, position) = self.position.step(index_fn(part), &index_fn)?;
+        Some(Cursor { position, alphabet: self.alphabet, alphabet_size: self.alphabet_size })
+    }
+
+    /// whether a stored element ends at the cursor
+    pub fn is_terminal(&self) -> bool {
+        self.position.value().is_some()
+    }
+
+    /// number of times the element ending at the cursor was inserted, 0 if none ends there
+    pub fn frequency(&self) -> usize {
+        self.position.value().copied().unwrap_or(0)
+    }
+
+    /// number of distinct parts continuing some stored element past the cursor, 1 inside a run
+    pub fn num_children(&self) -> usize {
+        self.position.len()
+    }
+}
+
+/// how the parts pushed into a `PrefixMatcher` relate to the elements of its trie
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchState {
+    /// no stored element starts with the pushed parts
+    NoMatch,
+    /// stored elements start with the pushed parts, none of them ends there
+    Prefix,
+    /// the pushed parts are a stored element, which may be a prefix of others
+    Exact,
+}
+
+/// the position reached by the parts typed so far, kept between keystrokes, see
+/// `Trie::prefix_matcher`
+///
+/// every pushed part advances from where the last one left off rather than from the root, and
+/// `pop` steps back to where the part before was
+pub struct PrefixMatcher<'a, TParts, A: Alphabet<TParts>> {
+    /// the stored part of every matched pushed part, along with the position it leads to
+    path: Vec<(&'a TParts, Position<'a, TParts, usize>)>,
+    root: Position<'a, TParts, usize>,
+    /// parts pushed past the point where nothing matched anymore
+    unmatched: usize,
+    alphabet: &'a A,
+    alphabet_size: usize,
+}
+
+impl<'a, TParts, A: Alphabet<TParts>> PrefixMatcher<'a, TParts, A> {
+    fn position(&self) -> Position<'a, TParts, usize> {
+        self.path.last().map_or(self.root, |&(_, position)| position)
+    }
+
+    /// appends `part` to the pushed parts, returns the state reached
+    ///
+    /// a part the alphabet skips is not pushed at all, counting for neither `len` nor `pop`
+    pub fn push(&mut self, part: &TParts) -> MatchState {
+        if A::SKIPS && self.alphabet.skips(part) {
+            return self.state();
+        }
+        let index_fn = checked_index(self.alphabet, self.alphabet_size);
+        let pos = index_fn(part);
+        let step = if self.unmatched == 0 { self.position().step(pos, &index_fn) } else { None };
+        match step {
+            Some(step) => self.path.push(step),
+            None => self.unmatched += 1,
+        }
+        self.state()
+    }
+
+    /// removes the last pushed part, returns the state reached, that of no parts at all once
+    /// everything was removed
+    pub fn pop(&mut self) -> MatchState {
+        if self.unmatched > 0 {
+            self.unmatched -= 1;
+        } else {
+            self.path.pop();
+        }
+        self.state()
+    }
+
+    /// removes every pushed part
+    pub fn clear(&mut self) {
+        self.path.clear();
+        self.unmatched = 0;
+    }
+
+    /// number of pushed parts, matched or not
+    pub fn len(&self) -> usize {
+        self.path.len() + self.unmatched
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn state(&self) -> MatchState {
+        let position = self.position();
+        if self.unmatched > 0 || matches!(position, Position::Node(Node::Empty)) {
+            MatchState::NoMatch
+        } else if position.value().is_some() {
+            MatchState::Exact
+        } else {
+            MatchState::Prefix
+        }
+    }
+
+    /// iterates over every stored element starting with the pushed parts, in the same order as
+    /// `Trie::iter`, nothing if they match no element
+    ///
+    /// yielded elements hold the stored parts, the pushed ones included
+    pub fn completions(&self) -> impl Iterator<Item=Vec<TParts>> + 'a where TParts: Clone {
+        let found = if self.unmatched > 0 {
+            None
+        } else {
+            let mut prefix = self.path.iter().map(|&(part, _)| part.clone()).collect::<Vec<_>>();
+            let node = match self.position() {
+                Position::Node(node) => node,
+                Position::Run(rest, child) => {
+                    prefix.extend(rest.iter().cloned());
+                    child
+                }
+            };
+            Some(Iter::new(prefix, node))
+        };
+        found.into_iter().flatten().map(|(parts, _)| parts)
+    }
+}
+
+/// depth first traversal consuming the elements of a `Trie`, in the same order as `Trie::iter`
+pub struct IntoIter<TParts> {
+    stack: Vec<(Vec<TParts>, Node<TParts, usize>)>,
+}
+
+impl<TParts: Clone> Iterator for IntoIter<TParts> {
+    type Item = Vec<TParts>;
+
+    fn next(&mut self) -> Option<Vec<TParts>> {
+        while let Some((mut prefix, node)) = self.stack.pop() {
+            match node {
+                Node::Empty => {}
+                Node::Leaf(_) => return Some(prefix),
+                Node::Normal { children, value } => {
+                    for (part, child) in children.into_vec().into_iter().rev() {
+                        let mut child_prefix = prefix.clone();
+                        child_prefix.push(part);
+                        self.stack.push((child_prefix, child));
+                    }
+                    if value.is_some() {
+                        return Some(prefix);
+                    }
+                }
+                Node::Compressed { compressed, child, value } => {
+                    if value.is_some() {
+                        let mut child_prefix = prefix.clone();
+                        child_prefix.extend(compressed);
+                        self.stack.push((child_prefix, *child));
+                        return Some(prefix);
+                    }
+                    prefix.extend(compressed);
+                    self.stack.push((prefix, *child));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// a completion candidate, ordered such that the greatest is the worst candidate: least frequent,
+/// then latest in iteration order
+struct Ranked<T> {
+    frequency: usize,
+    order: usize,
+    parts: Vec<T>,
+}
+
+impl<T> Ranked<T> {
+    fn key(&self) -> (Reverse<usize>, usize) {
+        (Reverse(self.frequency), self.order)
+    }
+}
+
+impl<T> PartialEq for Ranked<T> {
+    fn eq(&self, other: &Ranked<T>) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl<T> Eq for Ranked<T> {}
+
+impl<T> PartialOrd for Ranked<T> {
+    fn partial_cmp(&self, other: &Ranked<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Ranked<T> {
+    fn cmp(&self, other: &Ranked<T>) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// A generic tree based collection storing decomposed items
+///
+/// A generic tree based fixed width per node tree in which inserted elements are decomposed into
+/// their parts and stored such that shared prefixes are reused. Optimization used for nodes with
+/// single child such that nodes until a future split are condensed into a single node.
+///
+/// AKA "prefix tree", "trie"
+///
+/// # Examples
+///
+/// ```
+/// let mut trie = Trie::new(AsciiLower);
+/// assert_eq!(trie.contains("asd"), false);
+/// trie.insert("asd".to_string());
+/// assert_eq!(trie.contains("asd"), true);
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Trie<TParts, A: Alphabet<TParts>> {
+    /// every stored element holds the number of times it was inserted
+    pub(crate) root: Node<TParts, usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    alphabet: A,
+    /// `alphabet.size()`, held for building nodes and serialization
+    alphabet_size: usize,
+    count: usize,
+    /// runs of fewer parts built by insertion are kept as chained `Normal` nodes
+    #[cfg_attr(feature = "serde", serde(skip))]
+    min_compress_len: usize,
+}
+
+impl<TParts, F: Fn(&TParts) -> usize> Trie<TParts, FnAlphabet<F>> {
+    /// creates an empty trie over an alphabet of `alphabet_size` symbols given by `index_fn`
+    ///
+    /// `alphabet_size` is a count rather than the largest index, `index_fn` must map every part into
+    /// `0..alphabet_size`, see `Trie::new`. the trie is `Clone` whenever `index_fn` is, as plain
+    /// `fn` pointers and closures capturing nothing are, a `fn(&TParts) -> usize` giving a type that
+    /// can be named in struct fields
+    pub fn from_fn(index_fn: F, alphabet_size: usize) -> Trie<TParts, FnAlphabet<F>> {
+        Trie::new(FnAlphabet::new(index_fn, alphabet_size))
+    }
+
+    /// creates an empty trie ending every element with a terminator, `terminator_index` being
+    /// reserved for it out of the alphabet given by `index_fn`, see `TerminatedTrie`
+    pub fn with_terminator(index_fn: F, alphabet_size: usize, terminator_index: usize) -> TerminatedTrie<TParts, FnAlphabet<F>> {
+        TerminatedTrie::from_fn(index_fn, alphabet_size, terminator_index)
+    }
+}
+
+impl<TParts, F: Fn(&TParts) -> Option<usize>> Trie<TParts, SkipFnAlphabet<F>> {
+    /// creates an empty trie over an alphabet of `alphabet_size` symbols given by `index_fn`, parts
+    /// it returns `None` for being skipped
+    ///
+    /// skipped parts are left out of elements as they are inserted and of every query alike, such
+    /// that "a-b" and "ab" are the same element when `index_fn` skips '-'
+    pub fn from_skipping_fn(index_fn: F, alphabet_size: usize) -> Trie<TParts, SkipFnAlphabet<F>> {
+        Trie::new(SkipFnAlphabet::new(index_fn, alphabet_size))
+    }
+}
+
+impl Trie<char, AsciiLower> {
+    /// creates an empty string trie over the 26 latin letters, ignoring case
+    ///
+    /// strings holding any other `char` panic on insertion, see `Trie::bytes` for arbitrary strings
+    pub fn ascii_lowercase() -> Trie<char, AsciiLower> {
+        Trie::new(AsciiLower)
+    }
+}
+
+impl Trie<bool, Binary> {
+    /// creates an empty binary trie, branching on a bit at a time of keys decomposed with `Bits`
+    pub fn binary() -> Trie<bool, Binary> {
+        Trie::new(Binary)
+    }
+}
+
+impl Trie<u8, Bytes> {
+    /// creates an empty string trie over the utf-8 bytes of its strings, holding any string
+    pub fn bytes() -> Trie<u8, Bytes> {
+        Trie::new(Bytes)
+    }
+}
+
+impl<TParts, A: Alphabet<TParts>> Trie<TParts, A> {
+    /// creates an empty trie over `alphabet`
+    ///
+    /// every part is checked to be mapped into the alphabet as it is inserted or queried, panicking
+    /// on an index out of range, see `try_insert` for a fallible alternative. an alphabet of size 0
+    /// has no index in range, leaving the empty element as the only one that can be stored
+    ///
+    /// parts mapped to the same index are the same symbol to the trie. every edge keeps the part it
+    /// was first inserted with as the representative of its symbol, which is what iteration
+    /// reconstructs elements from
+    pub fn new(alphabet: A) -> Trie<TParts, A> {
+        let new_node = Node::new_empty();
+        let alphabet_size = alphabet.size();
+        Trie { root: new_node, alphabet, alphabet_size, count: 0, min_compress_len: 1 }
+    }
+
+    /// creates an empty trie whose root already branches over every symbol of the alphabet
+    ///
+    /// helps where elements spread over the alphabet from their first part on, such as bytes of
+    /// uniformly distributed keys, sparing the root being built as a run and split again over the
+    /// first inserts. hurts where elements share a first part, as that root then holds a slot for
+    /// every symbol where a single run would do. the root is allocated densely whatever the size of
+    /// the alphabet, and goes back to being built on demand once `clear`ed or emptied by removals
+    pub fn with_root_fanout(alphabet: A) -> Trie<TParts, A> {
+        let alphabet_size = alphabet.size();
+        let root = Node::Normal { children: Children::new_dense(alphabet_size), value: None };
+        Trie { root, alphabet, alphabet_size, count: 0, min_compress_len: 1 }
+    }
+
+    /// creates an empty trie over `alphabet` compressing only runs of at least `min_compress_len`
+    /// parts, see `Trie::new`
+    ///
+    /// tries otherwise compress every run, as does a `min_compress_len` of 0 or 1. shorter runs
+    /// built as elements are inserted get a `Normal` node per part instead, taking a slot per symbol
+    /// of the alphabet but sparing lookups the comparison of the run part by part. runs rebuilt by
+    /// removals, set operations, merges or `from_sorted_iter` are compressed whatever their length
+    pub fn with_min_compress_len(alphabet: A, min_compress_len: usize) -> Trie<TParts, A> {
+        Trie { min_compress_len, ..Trie::new(alphabet) }
+    }
+
+    /// creates a trie over `alphabet` holding every element of `iter`
+    pub fn from_iter_with<T: Decomposable<TParts>, I: IntoIterator<Item=T>>(alphabet: A, iter: I) -> Trie<TParts, A> {
+        let mut trie = Trie::new(alphabet);
+        trie.extend(iter);
+        trie
+    }
+
+    /// creates a trie over `alphabet` holding every element of `iter`, which must be sorted by the
+    /// indices of their parts as `iter` orders them, duplicates adjacent
+    ///
+    /// the tree is built in a single pass over the sorted elements, each node created in its final
+    /// shape rather than split and promoted as elements are inserted one by one, and is the same
+    /// tree insertion would build. every element is checked to follow the one before, which the
+    /// build compares against anyway
+    ///
+    /// # Panics
+    ///
+    /// panics on unsorted input, in release builds as in debug ones
+    pub fn from_sorted_iter<T: Decomposable<TParts>, I: IntoIterator<Item=T>>(alphabet: A, iter: I) -> Trie<TParts, A> {
+        let alphabet_size = alphabet.size();
+        let (root, count) = build_sorted(iter.into_iter().map(|t| kept(t.decompose(), &alphabet)), &checked_index(&alphabet, alphabet_size), alphabet_size);
+        Trie { root, alphabet, alphabet_size, count, min_compress_len: 1 }
+    }
+
+    /// number of elements stored in the trie
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// removes every element, keeping the alphabet for reuse
+    pub fn clear(&mut self) {
+        self.root = Node::new_empty();
+        self.count = 0;
+    }
+
+    /// removes every element, yielding them in the same order as `iter`
+    ///
+    /// the elements are gathered up front and the trie is empty as soon as this returns, whether or
+    /// not the iterator is consumed. parts of shared prefixes are cloned into each element
+    pub fn drain(&mut self) -> impl Iterator<Item=Vec<TParts>> where TParts: Clone {
+        let drained = self.iter().collect::<Vec<_>>();
+        self.clear();
+        drained.into_iter()
+    }
+
+    /// releases the spare capacity of the trie, meant to be run once after a batch of inserts
+    ///
+    /// runs are shrunk to their length and `Normal` nodes with at most a quarter of the alphabet
+    /// occupied switch to holding only their occupied positions, at the cost of a slower lookup
+    /// through them
+    pub fn shrink_to_fit(&mut self) {
+        self.root.shrink_to_fit();
+    }
+
+    /// counts the nodes of the trie by kind, walking every node once
+    ///
+    /// meant for diagnosing the shape and memory use of a trie, see `tree` for the nodes themselves
+    pub fn stats(&self) -> TrieStats {
+        let mut stats = TrieStats::default();
+        self.root.stats(0, &mut stats);
+        stats
+    }
+
+    /// walks every node of the trie, checking that it is well formed
+    ///
+    /// a run is never empty nor directly followed by a run without a value, which would be one
+    /// run, dense children have a slot for every symbol of the alphabet, every child is held at the
+    /// index of its part and the terminal nodes match the number of elements. the error describes
+    /// the first violation found along with the part indices leading to it. meant for tests and for
+    /// tracking down corruption, as it visits every node
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let index_fn = |part: &TParts| self.alphabet.index(part);
+        let terminals = self.root.check_invariants(&mut Vec::new(), &index_fn, self.alphabet_size)?;
+        if terminals != self.count {
+            return Err(alloc::format!("{} terminal nodes for {} elements", terminals, self.count));
+        }
+        Ok(())
+    }
+
+    /// number of parts of all stored elements together, as if every element were stored on its own
+    ///
+    /// elements inserted more than once are counted once, see `stored_parts` for the parts the
+    /// nodes hold once shared prefixes and runs are accounted for
+    pub fn total_parts(&self) -> usize {
+        self.root.part_counts(0).0
+    }
+
+    /// number of parts held by the nodes of the trie, those of runs and of branching nodes alike
+    ///
+    /// every part shared by the elements below it is counted once, such that the ratio to
+    /// `total_parts` is how much the tree saves over storing elements on their own
+    pub fn stored_parts(&self) -> usize {
+        self.root.part_counts(0).1
+    }
+
+    /// estimates the bytes used by the trie, the trie itself along with everything its nodes allocate
+    ///
+    /// an approximation, counting the capacity allocated for slots and runs but not the overhead of
+    /// the allocator or the bookkeeping of sparse children, nor anything the alphabet or the parts
+    /// allocate themselves. parts and children held inline by a node are counted as part of it
+    pub fn memory_usage(&self) -> usize {
+        mem::size_of::<Trie<TParts, A>>() + self.root.heap_size()
+    }
+
+    /// inserts an element, returns whether it was newly added
+    ///
+    /// inserting an element already stored increments its frequency instead
+    pub fn insert<T: Decomposable<TParts>>(&mut self, t: T) -> bool {
+        self.insert_parts(t.decompose())
+    }
+
+    fn insert_parts<TIt: Iterator<Item=TParts>>(&mut self, it: TIt) -> bool {
+        let index_fn = checked_index(&self.alphabet, self.alphabet_size);
+        // every part is checked, including those only stored in a run and indexed by a later split
+        let mut it = kept(it, &self.alphabet).inspect(|part| { index_fn(part); }).peekable();
+        let increment = |frequency: &mut usize, _| mem::replace(frequency, *frequency + 1);
+        let added = self.root.upsert(&mut it, 1, increment, &index_fn, self.alphabet_size, self.min_compress_len).is_none();
+        if added {
+            self.count += 1;
+        }
+        added
+    }
+
+    /// inserts every line of `reader` as a `String`, returns the number of lines newly added
+    ///
+    /// lines are read one at a time rather than the whole input up front. line endings, `\n` or
+    /// `\r\n`, are not part of the inserted lines and empty lines are skipped, such that a trailing
+    /// newline or blank separators never insert the empty element. stops at the first read error,
+    /// keeping the lines inserted before it
+    #[cfg(feature = "std")]
+    pub fn insert_lines<R: std::io::BufRead>(&mut self, reader: R) -> std::io::Result<usize> where String: Decomposable<TParts> {
+        let mut added = 0;
+        for line in reader.lines() {
+            let line = line?;
+            if !line.is_empty() && self.insert(line) {
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// number of times an element was inserted, 0 if it is not stored
+    pub fn frequency<T: Decomposable<TParts>>(&self, t: T) -> usize {
+        self.root.get(kept(t.decompose(), &self.alphabet), &checked_index(&self.alphabet, self.alphabet_size)).copied().unwrap_or(0)
+    }
+
+    pub fn contains<T: Decomposable<TParts>>(&self, t: T) -> bool {
+        self.root.get(kept(t.decompose(), &self.alphabet), &checked_index(&self.alphabet, self.alphabet_size)).is_some()
+    }
+
+    /// whether every element of `keys` is stored, stopping at the first that is not
+    ///
+    /// true for no keys at all
+    pub fn contains_all<T: Decomposable<TParts>, I: IntoIterator<Item=T>>(&self, keys: I) -> bool {
+        keys.into_iter().all(|t| self.contains(t))
+    }
+
+    /// whether any element of `keys` is stored, stopping at the first that is
+    ///
+    /// false for no keys at all
+    pub fn contains_any<T: Decomposable<TParts>, I: IntoIterator<Item=T>>(&self, keys: I) -> bool {
+        keys.into_iter().any(|t| self.contains(t))
+    }
+
+    /// the stored parts of the element `query` matches, `None` if it is not a member
+    ///
+    /// as parts are matched by their index alone, the stored parts can differ from those of
+    /// `query`, such as the case of a case insensitive alphabet. they are the first inserted parts
+    /// of every symbol along the way, as with `iter`
+    pub fn get_key<T: Decomposable<TParts>>(&self, query: T) -> Option<Vec<TParts>> where TParts: Clone {
+        let mut len = 0;
+        let (path, node) = self.root.find_prefix(kept(query.decompose(), &self.alphabet).inspect(|_| len += 1), &checked_index(&self.alphabet, self.alphabet_size))?;
+        // a query ending inside a run is found past the run
+        if path.len() == len && node.value().is_some() {
+            Some(path.into_iter().cloned().collect())
+        } else {
+            None
+        }
+    }
+
+    /// same as `insert` but returns an error instead of panicking when the alphabet maps a part
+    /// outside of the alphabet, in which case the trie is left untouched
+    ///
+    /// the element is fully decomposed and validated before being inserted
+    pub fn try_insert<T: Decomposable<TParts>>(&mut self, t: T) -> Result<bool, TrieError> {
+        let parts = validated(t.decompose(), &self.alphabet, self.alphabet_size)?;
+        Ok(self.insert(parts))
+    }
+
+    /// inserts every element of `iter` or none of them, returns the number newly added
+    ///
+    /// every element is decomposed and validated before any is inserted, such that on the error of
+    /// the first element mapping a part outside of the alphabet the trie is left untouched, its
+    /// elements, frequencies and nodes alike. the whole batch is held decomposed in the meantime
+    pub fn try_insert_batch<T: Decomposable<TParts>, I: IntoIterator<Item=T>>(&mut self, iter: I) -> Result<usize, TrieError> {
+        let batch = iter.into_iter()
+            .map(|t| validated(t.decompose(), &self.alphabet, self.alphabet_size))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut added = 0;
+        for parts in batch {
+            if self.insert(parts) {
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+
+    /// same as `contains` but returns an error instead of panicking when the alphabet maps a part
+    /// outside of the alphabet
+    pub fn try_contains<T: Decomposable<TParts>>(&self, t: T) -> Result<bool, TrieError> {
+        let parts = validated(t.decompose(), &self.alphabet, self.alphabet_size)?;
+        Ok(self.contains(parts))
+    }
+
+    /// same as `contains` but decomposes the element by reference, leaving it with the caller
+    pub fn contains_ref<'a, T: DecomposableRef<'a, TParts>>(&self, t: &'a T) -> bool {
+        self.root.get(kept(t.decompose_ref(), &self.alphabet), &checked_index(&self.alphabet, self.alphabet_size)).is_some()
+    }
+
+    /// same as `contains` but goes over parts lent by the element, no part is copied
+    pub fn contains_borrowed<'a, T: DecomposeRef<'a, TParts> + ?Sized>(&self, t: &'a T) -> bool where TParts: 'a {
+        self.root.get(kept(t.parts(), &self.alphabet), &checked_index(&self.alphabet, self.alphabet_size)).is_some()
+    }
+
+    /// same as `insert` but goes over parts lent by the element, cloning them one at a time rather
+    /// than taking the element
+    pub fn insert_borrowed<'a, T: DecomposeRef<'a, TParts> + ?Sized>(&mut self, t: &'a T) -> bool where TParts: Clone + 'a {
+        self.insert_parts(t.parts().cloned())
+    }
+
+    /// removes an element from the trie, returns whether it was present
+    ///
+    /// nodes freed by the removal are collapsed such that a `Normal` node left with a single child
+    /// is compressed back into its parent run
+    pub fn remove<T: Decomposable<TParts>>(&mut self, t: T) -> bool {
+        let removed = self.root.remove(&mut kept(t.decompose(), &self.alphabet).peekable(), &checked_index(&self.alphabet, self.alphabet_size)).is_some();
+        if removed {
+            self.count -= 1;
+        }
+        removed
+    }
+
+    /// keeps only the elements for which `f` returns true, given their decomposed parts
+    ///
+    /// removal happens in a single walk of the trie, collapsing nodes the same as `remove`
+    pub fn retain<F: FnMut(&[TParts]) -> bool>(&mut self, mut f: F) where TParts: Clone {
+        self.count -= self.root.retain(&mut Vec::new(), &mut |parts, _| f(parts));
+    }
+
+    /// iterates over the decomposed parts of every stored element
+    ///
+    /// elements are yielded depth first, children visited by ascending index such that the order is
+    /// deterministic and an element comes before any element it is a prefix of
+    pub fn iter(&self) -> impl Iterator<Item=Vec<TParts>> + '_ where TParts: Clone {
+        Iter::new(Vec::new(), &self.root).map(|(parts, _)| parts)
+    }
+
+    /// hands the parts of every stored element to `f`, in the same order as `iter`
+    ///
+    /// the parts are built up in a single buffer reused across elements, such that no element is
+    /// allocated for, meant for inspecting elements that need not outlive the call
+    pub fn for_each_key<F: FnMut(&[TParts])>(&self, mut f: F) where TParts: Clone {
+        self.root.for_each_key(&mut Vec::new(), &mut f);
+    }
+
+    /// iterates over every stored element starting with `prefix`, in the same order as `iter`
+    ///
+    /// yielded elements hold the stored parts, `prefix` included
+    pub fn keys_with_prefix<T: Decomposable<TParts>>(&self, prefix: T) -> impl Iterator<Item=Vec<TParts>> + '_ where TParts: Clone {
+        self.root.find_prefix(kept(prefix.decompose(), &self.alphabet), &checked_index(&self.alphabet, self.alphabet_size))
+            .map(|(path, node)| Iter::new(path.into_iter().cloned().collect(), node))
+            .into_iter()
+            .flatten()
+            .map(|(parts, _)| parts)
+    }
+
+    /// iterates over the paths of the trie cut at `max_depth` parts, in the same order as `iter`
+    ///
+    /// every element of at most `max_depth` parts is yielded whole, along with `false`. elements
+    /// running past the limit are yielded once per distinct path of `max_depth` parts, along with
+    /// `true` to mark elements having been cut off; at the limit, an element ending there is
+    /// yielded only once, marked as cut if anything runs past it
+    pub fn iter_to_depth(&self, max_depth: usize) -> impl Iterator<Item=(Vec<TParts>, bool)> + '_ where TParts: Clone {
+        DepthIter::new(&self.root, max_depth)
+    }
+
+    /// iterates over the elements front coded, in the same order as `iter`
+    ///
+    /// every element is yielded as the number of parts it shares with the element before it, 0 for
+    /// the first, followed by the parts past those. appending them to the kept parts of the element
+    /// before reconstructs the element
+    pub fn front_coded_iter(&self) -> impl Iterator<Item=(usize, Vec<TParts>)> + '_ where TParts: Clone {
+        FrontCodedIter::new(&self.root)
+    }
+
+    /// a read-only view of the root node, for traversals of the nodes themselves such as exports
+    ///
+    /// views borrow the trie, children being viewed as they are reached
+    pub fn root_view(&self) -> NodeView<'_, TParts> {
+        NodeView::new(&self.root)
+    }
+
+    /// a cursor at the root of the trie, to be advanced part by part with `Cursor::child`
+    pub fn cursor(&self) -> Cursor<'_, TParts, A> {
+        Cursor { position: Position::Node(&self.root), alphabet: &self.alphabet, alphabet_size: self.alphabet_size }
+    }
+
+    /// a matcher at the root of the trie, to which the parts of a prefix are pushed one at a time
+    /// as they come in, such as while typing
+    pub fn prefix_matcher(&self) -> PrefixMatcher<'_, TParts, A> {
+        let root = Position::Node(&self.root);
+        PrefixMatcher { path: Vec::new(), root, unmatched: 0, alphabet: &self.alphabet, alphabet_size: self.alphabet_size }
+    }
+
+    /// whether any stored element begins with `prefix`, which need not be a member itself
+    pub fn starts_with<T: Decomposable<TParts>>(&self, prefix: T) -> bool {
+        // the root is the only node which may be left without anything below it
+        self.count > 0 && self.root.find_prefix(kept(prefix.decompose(), &self.alphabet), &checked_index(&self.alphabet, self.alphabet_size))
+            .is_some_and(|(_, node)| !node.is_empty())
+    }
+
+    /// number of stored elements starting with `prefix`
+    ///
+    /// subtree counts are not cached on the nodes, so this walks every node below `prefix`
+    pub fn count_prefix<T: Decomposable<TParts>>(&self, prefix: T) -> usize {
+        self.root.find_prefix(kept(prefix.decompose(), &self.alphabet), &checked_index(&self.alphabet, self.alphabet_size))
+            .map_or(0, |(_, node)| node.count())
+    }
+
+    /// removes every element starting with `prefix`, returns how many were removed
+    ///
+    /// the elements are dropped with the node holding them rather than removed one by one, with the
+    /// nodes left behind collapsed the same as `remove`. see `split_off_prefix` to keep them
+    pub fn remove_prefix<T: Decomposable<TParts>>(&mut self, prefix: T) -> usize {
+        let removed = self.root.take_prefix(&mut kept(prefix.decompose(), &self.alphabet).peekable(), &checked_index(&self.alphabet, self.alphabet_size))
+            .map_or(0, |taken| taken.count());
+        self.count -= removed;
+        removed
+    }
+
+    /// moves every element of `other` into `self`, summing the frequencies of elements held by both
+    ///
+    /// unlike `union`, nothing is cloned: the nodes of `other` are moved over, subtrees wholesale
+    /// where `self` holds nothing at their position. both tries are expected to share an alphabet,
+    /// that of `other` is dropped
+    ///
+    /// # Panics
+    ///
+    /// panics if the alphabets of the tries differ in size
+    pub fn merge(&mut self, other: Trie<TParts, A>) {
+        assert_eq!(self.alphabet_size, other.alphabet_size, "merged tries must share an alphabet size");
+        self.merge_root(other.root, other.count);
+    }
+
+    /// moves the `count` elements below `root` into the trie, see `merge`
+    fn merge_root(&mut self, root: Node<TParts, usize>, count: usize) {
+        let mut shared = 0;
+        let index_fn = checked_index(&self.alphabet, self.alphabet_size);
+        self.root.merge(root, &mut |frequency, other_frequency| {
+            *frequency += other_frequency;
+            shared += 1;
+        }, &index_fn, self.alphabet_size);
+        self.count += count - shared;
+    }
+
+    /// every element held by `self` but not by `older`, in the same order as `iter`
+    ///
+    /// both tries are walked at once rather than looking up every element of `self` in `older`.
+    /// both tries are expected to share an alphabet, the yielded elements hold the stored
+    /// parts of `self`
+    ///
+    /// # Panics
+    ///
+    /// panics if the alphabets of the tries differ in size
+    pub fn added_since(&self, older: &Trie<TParts, A>) -> Vec<Vec<TParts>> where TParts: Clone {
+        assert_eq!(self.alphabet_size, older.alphabet_size, "compared tries must share an alphabet size");
+        let mut found = Vec::new();
+        let index_fn = checked_index(&self.alphabet, self.alphabet_size);
+        added(Position::Node(&self.root), Some(Position::Node(&older.root)), &index_fn, &mut Vec::new(), &mut found);
+        found.into_iter().map(|path| path.into_iter().cloned().collect()).collect()
+    }
+
+    /// the `k` most frequently inserted elements starting with `prefix`, most frequent first
+    ///
+    /// elements inserted equally often keep the order of `iter`. only the best `k` are held while
+    /// walking the elements below `prefix` rather than sorting all of them
+    pub fn top_k_completions<T: Decomposable<TParts>>(&self, prefix: T, k: usize) -> Vec<Vec<TParts>> where TParts: Clone {
+        if k == 0 {
+            return Vec::new();
+        }
+        let found = self.root.find_prefix(kept(prefix.decompose(), &self.alphabet), &checked_index(&self.alphabet, self.alphabet_size));
+        let mut best = BinaryHeap::with_capacity(k + 1);
+        for (order, (parts, &frequency)) in found.into_iter().flat_map(|(path, node)| Iter::new(path.into_iter().cloned().collect(), node)).enumerate() {
+            best.push(Ranked { frequency, order, parts });
+            if best.len() > k {
+                best.pop();
+            }
+        }
+        best.into_sorted_vec().into_iter().map(|ranked| ranked.parts).collect()
+    }
+
+    /// every stored element within a levenshtein distance of `max_distance` from `query`, counting
+    /// substitutions, insertions and deletions of single parts, in the same order as `iter`
+    pub fn fuzzy_contains<T: Decomposable<TParts>>(&self, query: T, max_distance: usize) -> Vec<Vec<TParts>> where TParts: Clone {
+        let index_fn = checked_index(&self.alphabet, self.alphabet_size);
+        let query = kept(query.decompose(), &self.alphabet).map(|part| index_fn(&part)).collect::<Vec<_>>();
+        let row = (0..=query.len()).collect::<Vec<_>>();
+        let mut found = Vec::new();
+        self.root.fuzzy(&query, max_distance, &index_fn, &mut Vec::new(), &row, &mut found);
+        found.into_iter().map(|path| path.into_iter().cloned().collect()).collect()
+    }
+
+    /// every stored element of the same length as `pattern` whose parts match it slot by slot, in
+    /// the same order as `iter`
+    ///
+    /// a `None` slot is a wildcard matching any single part, a `Some` slot only matches the parts its
+    /// predicate accepts
+    pub fn matches_pattern<F: Fn(&TParts) -> bool>(&self, pattern: &[Option<F>]) -> Vec<Vec<TParts>> where TParts: Clone {
+        let mut found = Vec::new();
+        self.root.matching(pattern, &mut Vec::new(), &mut found);
+        found.into_iter().map(|path| path.into_iter().cloned().collect()).collect()
+    }
+
+    /// iterates over the stored elements in `lo..hi`, in the same order as `iter`
+    ///
+    /// elements are ordered lexicographically by the indices of their parts, a prefix coming before
+    /// the elements it is a prefix of. subtrees out of range are skipped without being walked
+    pub fn range<T: Decomposable<TParts>>(&self, lo: T, hi: T) -> impl Iterator<Item=Vec<TParts>> + '_ where TParts: Clone {
+        let index_fn = checked_index(&self.alphabet, self.alphabet_size);
+        let lo = kept(lo.decompose(), &self.alphabet).map(|part| index_fn(&part)).collect::<Vec<_>>();
+        let hi = kept(hi.decompose(), &self.alphabet).map(|part| index_fn(&part)).collect::<Vec<_>>();
+        let mut found = Vec::new();
+        self.root.range(Bounds { lo: Some(&lo), hi: Some(&hi) }, &index_fn, &mut Vec::new(), &mut found);
+        found.into_iter().map(|path| path.into_iter().cloned().collect())
+    }
+
+    /// the longest stored element which is a prefix of `query`, `query` itself included
+    pub fn longest_prefix_match<T: Decomposable<TParts>>(&self, query: T) -> Option<Vec<TParts>> where TParts: Clone {
+        self.root.longest_prefix(kept(query.decompose(), &self.alphabet), &checked_index(&self.alphabet, self.alphabet_size))
+            .map(|(path, _)| path.into_iter().cloned().collect())
+    }
+
+    /// whether any stored element is a prefix of `query`, `query` itself included
+    ///
+    /// stops at the first element found along the path of `query`, such as the shortest blocked
+    /// path above a url, see `longest_prefix_match` for the element itself
+    pub fn contains_prefix_of<T: Decomposable<TParts>>(&self, query: T) -> bool {
+        self.root.has_prefix_of(kept(query.decompose(), &self.alphabet), &checked_index(&self.alphabet, self.alphabet_size))
+    }
+
+    /// the least stored element, ordered by the indices of its parts as with `range`
+    pub fn first(&self) -> Option<Vec<TParts>> where TParts: Clone {
+        self.root.first().map(|path| path.into_iter().cloned().collect())
+    }
+
+    /// the greatest stored element, ordered by the indices of its parts as with `range`
+    pub fn last(&self) -> Option<Vec<TParts>> where TParts: Clone {
+        self.root.last().map(|path| path.into_iter().cloned().collect())
+    }
+
+    /// the least stored element greater than `key`, which need not be a member itself, ordered as
+    /// with `range`
+    pub fn successor<T: Decomposable<TParts>>(&self, key: T) -> Option<Vec<TParts>> where TParts: Clone {
+        let index_fn = checked_index(&self.alphabet, self.alphabet_size);
+        let query = kept(key.decompose(), &self.alphabet).map(|part| index_fn(&part)).collect::<Vec<_>>();
+        successor(Position::Node(&self.root), &query, &index_fn, &mut Vec::new())
+            .map(|path| path.into_iter().cloned().collect())
+    }
+
+    /// the greatest stored element less than `key`, see `successor`
+    pub fn predecessor<T: Decomposable<TParts>>(&self, key: T) -> Option<Vec<TParts>> where TParts: Clone {
+        let index_fn = checked_index(&self.alphabet, self.alphabet_size);
+        let query = kept(key.decompose(), &self.alphabet).map(|part| index_fn(&part)).collect::<Vec<_>>();
+        predecessor(Position::Node(&self.root), &query, &index_fn, &mut Vec::new())
+            .map(|path| path.into_iter().cloned().collect())
+    }
+
+    /// the longest sequence of parts every stored element starts with, empty for an empty trie
+    pub fn common_prefix(&self) -> Vec<TParts> where TParts: Clone {
+        self.root.common_prefix().into_iter().cloned().collect()
+    }
+
+    /// renders the node structure of the trie, one node per line indented below its parent
+    ///
+    /// meant for debugging, the exact format is not stable
+    pub fn tree(&self) -> impl fmt::Display + '_ where TParts: fmt::Debug {
+        TreeDisplay { root: &self.root }
+    }
+
+    /// renders the node structure of the trie as a Graphviz DOT digraph, to be drawn with `dot`
+    ///
+    /// every node is a vertex labeled by its kind, drawn as a double circle where an element ends.
+    /// edges out of `Normal` nodes are labeled by the index of their symbol, those out of
+    /// `Compressed` nodes by the parts of the run. meant for debugging, the exact output is not stable
+    pub fn to_dot(&self) -> String where TParts: fmt::Debug {
+        let mut out = String::from("digraph trie {\n");
+        write_dot(&self.root, &mut 0, &mut out).expect("writing to a String does not fail");
+        out.push_str("}\n");
+        out
+    }
+
+    /// prints the node structure of the trie to stdout, see `tree`
+    #[cfg(feature = "std")]
+    pub fn print_tree(&self) where TParts: fmt::Debug {
+        print!("{}", self.tree());
+    }
+}
+
+impl<TParts: Clone, A: Alphabet<TParts> + Clone> Trie<TParts, A> {
+    /// a trie holding the elements of either trie
+    ///
+    /// both tries are expected to share an alphabet, the alphabet of `self` is used for
+    /// the result. frequencies are taken from `self` for elements held by both
+    pub fn union(&self, other: &Trie<TParts, A>) -> Trie<TParts, A> {
+        self.combined(other, |in_self, in_other| in_self || in_other)
+    }
+
+    /// a trie holding the elements held by both tries, see `union`
+    pub fn intersection(&self, other: &Trie<TParts, A>) -> Trie<TParts, A> {
+        self.combined(other, |in_self, in_other| in_self && in_other)
+    }
+
+    /// a trie holding the elements of `self` which are not held by `other`, see `union`
+    pub fn difference(&self, other: &Trie<TParts, A>) -> Trie<TParts, A> {
+        self.combined(other, |in_self, in_other| in_self && !in_other)
+    }
+
+    fn combined<FKeep: Fn(bool, bool) -> bool>(&self, other: &Trie<TParts, A>, keep: FKeep) -> Trie<TParts, A> {
+        let index_fn = checked_index(&self.alphabet, self.alphabet_size);
+        let root = combine(Some(Position::Node(&self.root)), Some(Position::Node(&other.root)), &keep, &index_fn, self.alphabet_size);
+        let count = root.count();
+        Trie { root, alphabet: self.alphabet.clone(), alphabet_size: self.alphabet_size, count, min_compress_len: self.min_compress_len }
+    }
+
+    /// removes every element starting with `prefix`, returning them as a trie of their own, `None`
+    /// if no element starts with it
+    ///
+    /// the split off elements keep `prefix` in front, such that tries split by prefix hold the same
+    /// elements as the original together. the returned trie shares the alphabet of `self`, its
+    /// elements keep their frequencies and stored parts, those of `prefix` included
+    pub fn split_off_prefix<T: Decomposable<TParts>>(&mut self, prefix: T) -> Option<Trie<TParts, A>> {
+        if self.count == 0 {
+            return None;
+        }
+        let index_fn = checked_index(&self.alphabet, self.alphabet_size);
+        let prefix = kept(prefix.decompose(), &self.alphabet).collect::<Vec<_>>();
+        let (mut path, _) = self.root.find_prefix(prefix.iter(), &index_fn)?;
+        // a prefix ending inside a run is found past the run
+        path.truncate(prefix.len());
+        let path = path.into_iter().cloned().collect::<Vec<_>>();
+
+        let taken = self.root.take_prefix(&mut prefix.iter().peekable(), &index_fn)?;
+        let count = taken.count();
+        self.count -= count;
+        let root = Node::new_compressed(path.into_iter(), taken);
+        Some(Trie { root, alphabet: self.alphabet.clone(), alphabet_size: self.alphabet_size, count, min_compress_len: self.min_compress_len })
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<TParts: Send, A: Alphabet<TParts> + Sync> Trie<TParts, A> {
+    /// creates a trie over `alphabet` holding every element of `iter`, built in parallel, see
+    /// `par_extend`
+    pub fn from_par_iter_with<T, I>(alphabet: A, iter: I) -> Trie<TParts, A>
+        where T: Decomposable<TParts> + Send, I: rayon::iter::IntoParallelIterator<Item=T> {
+        let mut trie = Trie::new(alphabet);
+        trie.par_extend(iter);
+        trie
+    }
+
+    /// inserts every element of `iter`, building the subtrees below distinct first parts in
+    /// parallel
+    ///
+    /// elements are decomposed in parallel and grouped by the index of their first part, as
+    /// elements of different groups share no node past the root. every group is built into a
+    /// subtree of its own, which are then put under a single root and merged into the trie as
+    /// `merge` does. the result is the same as inserting the elements in order, their frequencies
+    /// and representative parts included. elements spread over many first parts gain the most,
+    /// those sharing a first part are built by a single thread
+    pub fn par_extend<T, I>(&mut self, iter: I)
+        where T: Decomposable<TParts> + Send, I: rayon::iter::IntoParallelIterator<Item=T> {
+        use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+        let alphabet_size = self.alphabet_size;
+        let min_compress_len = self.min_compress_len;
+        let index_fn = checked_index(&self.alphabet, alphabet_size);
+        let alphabet = &self.alphabet;
+        let elements = iter.into_par_iter().map(|t| kept(t.decompose(), alphabet).collect::<Vec<_>>()).collect::<Vec<_>>();
+
+        let mut empty = 0;
+        let mut groups = BTreeMap::new();
+        for parts in elements {
+            match parts.first() {
+                Some(part) => groups.entry(index_fn(part)).or_insert_with(Vec::new).push(parts),
+                None => empty += 1,
+            }
+        }
+
+        let increment = |frequency: &mut usize, _| mem::replace(frequency, *frequency + 1);
+        let built = groups.into_par_iter().map(|(pos, group)| {
+            let mut node = Node::new_empty();
+            let mut first_part = None;
+            let mut count = 0;
+            for parts in group {
+                let mut parts = parts.into_iter();
+                let part = parts.next().unwrap();
+                first_part.get_or_insert(part);
+                let mut it = parts.inspect(|part| { index_fn(part); }).peekable();
+                if node.upsert(&mut it, 1, increment, &index_fn, alphabet_size, min_compress_len).is_none() {
+                    count += 1;
+                }
+            }
+            (pos, first_part.unwrap(), node, count)
+        }).collect::<Vec<_>>();
+
+        let count = built.iter().map(|(_, _, _, count)| count).sum::<usize>() + usize::from(empty > 0);
+        let mut children = built.into_iter().map(|(pos, part, node, _)| (pos, part, node)).collect::<Vec<_>>();
+        let root = match children.len() {
+            0 => Node::Empty,
+            1 => {
+                let (_, part, node) = children.pop().unwrap();
+                Node::new_compressed(core::iter::once(part), node)
+            }
+            _ => Node::new_normal(children, alphabet_size),
+        };
+        let root = root.with_value(if empty > 0 { Some(empty) } else { None });
+        // ends the borrow of the alphabet before merging
+        drop(index_fn);
+        self.merge_root(root, count);
+    }
+}
+
+/// the node tree of a serialized `Trie`, to be turned back into a trie with `Trie::from_serialized`
+/// given the same alphabet
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+pub struct SerializedTrie<TParts> {
+    root: Node<TParts, usize>,
+    alphabet_size: usize,
+    count: usize,
+}
+
+#[cfg(feature = "serde")]
+impl<TParts, A: Alphabet<TParts>> Trie<TParts, A> {
+    /// rebuilds a trie from its deserialized node tree
+    ///
+    /// alphabets are not serialized, `alphabet` must be the one the trie was built with for the
+    /// deserialized trie to be correct. fails if its size differs from the serialized one, or if
+    /// the node tree breaks an invariant of the trie, see `check_invariants`, rather than trusting
+    /// the shape of its nodes and its count of elements
+    pub fn from_serialized(alphabet: A, data: SerializedTrie<TParts>) -> Result<Trie<TParts, A>, TrieError> {
+        let alphabet_size = alphabet.size();
+        if data.alphabet_size != alphabet_size {
+            return Err(TrieError::AlphabetMismatch { expected: alphabet_size, got: data.alphabet_size });
+        }
+        let trie = Trie { root: data.root, alphabet, alphabet_size, count: data.count, min_compress_len: 1 };
+        trie.check_invariants().map_err(|reason| TrieError::Malformed { reason })?;
+        Ok(trie)
+    }
+}
+
+/// consumes the trie, the parts held by its nodes are moved into the yielded elements and only
+/// those shared between elements are cloned
+impl<TParts: Clone, A: Alphabet<TParts>> IntoIterator for Trie<TParts, A> {
+    type Item = Vec<TParts>;
+    type IntoIter = IntoIter<TParts>;
+
+    fn into_iter(self) -> IntoIter<TParts> {
+        IntoIter { stack: vec![(Vec::new(), self.root)] }
+    }
+}
+
+/// tries are equal when they hold the same elements, regardless of the order they were inserted in,
+/// the shape of their nodes or the frequency of their elements. parts are compared by index
+impl<TParts, A: Alphabet<TParts>> PartialEq for Trie<TParts, A> {
+    fn eq(&self, other: &Trie<TParts, A>) -> bool {
+        let index_fn = checked_index(&self.alphabet, self.alphabet_size);
+        let other_index_fn = checked_index(&other.alphabet, other.alphabet_size);
+        self.count == other.count
+            && same_elements(Position::Node(&self.root), Position::Node(&other.root), &index_fn, &other_index_fn)
+    }
+}
+
+impl<TParts, A: Alphabet<TParts>> Eq for Trie<TParts, A> {}
+
+/// formats the elements of the trie as a set, in the order of `iter`
+impl<TParts: Clone + fmt::Debug, A: Alphabet<TParts>> fmt::Debug for Trie<TParts, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<TParts, A: Alphabet<TParts>, T: Decomposable<TParts>> Extend<T> for Trie<TParts, A> {
+    fn extend<I: IntoIterator<Item=T>>(&mut self, iter: I) {
+        for t in iter {
+            self.insert(t);
+        }
+    }
 }
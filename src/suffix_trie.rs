@@ -0,0 +1,98 @@
+use alloc::vec::Vec;
+
+use super::{Alphabet, Decomposable, FnAlphabet, Trie};
+
+/// A trie storing its elements back to front, such that elements sharing a suffix share a path
+///
+/// parts are reversed on the way in and back on the way out, every query and yielded element
+/// being in the original order.
+///
+/// # Examples
+///
+/// ```
+/// let mut trie = SuffixTrie::new(AsciiLower);
+/// trie.insert("testing");
+/// assert_eq!(trie.keys_with_suffix("ing").count(), 1);
+/// ```
+#[derive(Clone)]
+pub struct SuffixTrie<TParts, A: Alphabet<TParts>> {
+    trie: Trie<TParts, A>,
+}
+
+/// the parts of `t` back to front
+fn reversed<TParts, T: Decomposable<TParts>>(t: T) -> Vec<TParts> {
+    let mut parts = t.decompose().collect::<Vec<_>>();
+    parts.reverse();
+    parts
+}
+
+impl<TParts, F: Fn(&TParts) -> usize> SuffixTrie<TParts, FnAlphabet<F>> {
+    /// creates an empty suffix trie over an alphabet of `alphabet_size` symbols given by
+    /// `index_fn`, see `Trie::from_fn`
+    pub fn from_fn(index_fn: F, alphabet_size: usize) -> SuffixTrie<TParts, FnAlphabet<F>> {
+        SuffixTrie::new(FnAlphabet::new(index_fn, alphabet_size))
+    }
+}
+
+impl<TParts, A: Alphabet<TParts>> SuffixTrie<TParts, A> {
+    /// creates an empty suffix trie over `alphabet`, see `Trie::new`
+    pub fn new(alphabet: A) -> SuffixTrie<TParts, A> {
+        SuffixTrie { trie: Trie::new(alphabet) }
+    }
+
+    /// number of elements stored in the trie
+    pub fn len(&self) -> usize {
+        self.trie.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trie.is_empty()
+    }
+
+    /// removes every element, keeping the alphabet for reuse
+    pub fn clear(&mut self) {
+        self.trie.clear();
+    }
+
+    /// inserts an element, returns whether it was newly added
+    ///
+    /// the element is fully decomposed up front to be reversed
+    pub fn insert<T: Decomposable<TParts>>(&mut self, t: T) -> bool {
+        self.trie.insert(reversed(t))
+    }
+
+    pub fn contains<T: Decomposable<TParts>>(&self, t: T) -> bool {
+        self.trie.contains(reversed(t))
+    }
+
+    /// removes an element from the trie, returns whether it was present
+    pub fn remove<T: Decomposable<TParts>>(&mut self, t: T) -> bool {
+        self.trie.remove(reversed(t))
+    }
+
+    /// iterates over the decomposed parts of every stored element, ordered by their parts read
+    /// back to front
+    pub fn iter(&self) -> impl Iterator<Item=Vec<TParts>> + '_ where TParts: Clone {
+        self.trie.iter().map(reversed)
+    }
+
+    /// iterates over every stored element ending with `suffix`, in the same order as `iter`
+    ///
+    /// yielded elements hold the stored parts, `suffix` included
+    pub fn keys_with_suffix<T: Decomposable<TParts>>(&self, suffix: T) -> impl Iterator<Item=Vec<TParts>> + '_ where TParts: Clone {
+        self.trie.keys_with_prefix(reversed(suffix)).map(reversed)
+    }
+
+    /// number of stored elements ending with `suffix`
+    pub fn count_suffix<T: Decomposable<TParts>>(&self, suffix: T) -> usize {
+        self.trie.count_prefix(reversed(suffix))
+    }
+}
+
+impl<TParts, A: Alphabet<TParts>, T: Decomposable<TParts>> Extend<T> for SuffixTrie<TParts, A> {
+    fn extend<I: IntoIterator<Item=T>>(&mut self, iter: I) {
+        for t in iter {
+            self.insert(t);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use alloc::vec::Vec;
+use core::iter;
+
+use super::{Alphabet, Decomposable, FnAlphabet, Trie};
+
+/// the parts of `A` along with the terminator, `None`, at the index reserved for it
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Terminated<A> {
+    alphabet: A,
+    terminator: usize,
+}
+
+impl<TParts, A: Alphabet<TParts>> Alphabet<Option<TParts>> for Terminated<A> {
+    const SKIPS: bool = A::SKIPS;
+
+    fn index(&self, part: &Option<TParts>) -> usize {
+        match part {
+            Some(part) => {
+                let index = self.alphabet.index(part);
+                assert_ne!(index, self.terminator, "index {} is reserved for the terminator", index);
+                index
+            }
+            None => self.terminator,
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.alphabet.size()
+    }
+
+    fn skips(&self, part: &Option<TParts>) -> bool {
+        part.as_ref().is_some_and(|part| self.alphabet.skips(part))
+    }
+}
+
+/// parts already decomposed, handed on as they are
+struct Parts<I>(I);
+
+impl<I: Iterator> Decomposable<I::Item> for Parts<I> {
+    type Iterator = I;
+
+    fn decompose(self) -> I {
+        self.0
+    }
+}
+
+/// the parts of `t`, none of them the terminator
+fn parts<TParts, T: Decomposable<TParts>>(t: T) -> Parts<impl Iterator<Item=Option<TParts>>> {
+    Parts(t.decompose().map(Some))
+}
+
+/// the parts of `t` followed by the terminator
+fn terminated<TParts, T: Decomposable<TParts>>(t: T) -> Parts<impl Iterator<Item=Option<TParts>>> {
+    Parts(t.decompose().map(Some).chain(iter::once(None)))
+}
+
+/// the parts of a stored element, without its terminator
+fn unterminated<TParts>(parts: Vec<Option<TParts>>) -> Vec<TParts> {
+    parts.into_iter().flatten().collect()
+}
+
+/// A trie ending every element with a terminator part, reserved out of the alphabet
+///
+/// every element is stored as its parts followed by the terminator, such that elements only ever
+/// end in a leaf reached by the terminator and a prefix of an element is never a member by
+/// accident of the path. this is the textbook design, costing a symbol of the alphabet and a node
+/// per element where `Trie` marks the node an element ends at instead.
+#[derive(Clone)]
+pub struct TerminatedTrie<TParts, A: Alphabet<TParts>> {
+    trie: Trie<Option<TParts>, Terminated<A>>,
+}
+
+impl<TParts, F: Fn(&TParts) -> usize> TerminatedTrie<TParts, FnAlphabet<F>> {
+    /// creates an empty trie over an alphabet of `alphabet_size` symbols given by `index_fn`, see
+    /// `Trie::from_fn`, of which `terminator_index` is the terminator
+    pub fn from_fn(index_fn: F, alphabet_size: usize, terminator_index: usize) -> TerminatedTrie<TParts, FnAlphabet<F>> {
+        TerminatedTrie::new(FnAlphabet::new(index_fn, alphabet_size), terminator_index)
+    }
+}
+
+impl<TParts, A: Alphabet<TParts>> TerminatedTrie<TParts, A> {
+    /// creates an empty trie over `alphabet`, reserving `terminator_index` for the terminator
+    ///
+    /// parts are checked as they are inserted or queried, panicking on a part indexing to the
+    /// terminator as on any index out of range
+    pub fn new(alphabet: A, terminator_index: usize) -> TerminatedTrie<TParts, A> {
+        assert!(terminator_index < alphabet.size(), "terminator index {} is out of range for alphabet_size {}", terminator_index, alphabet.size());
+        TerminatedTrie { trie: Trie::new(Terminated { alphabet, terminator: terminator_index }) }
+    }
+
+    /// number of elements stored in the trie
+    pub fn len(&self) -> usize {
+        self.trie.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trie.is_empty()
+    }
+
+    /// removes every element, keeping the alphabet for reuse
+    pub fn clear(&mut self) {
+        self.trie.clear();
+    }
+
+    /// inserts an element followed by the terminator, returns whether it was newly added
+    pub fn insert<T: Decomposable<TParts>>(&mut self, t: T) -> bool {
+        self.trie.insert(terminated(t))
+    }
+
+    pub fn contains<T: Decomposable<TParts>>(&self, t: T) -> bool {
+        self.trie.contains(terminated(t))
+    }
+
+    /// removes an element from the trie, returns whether it was present
+    pub fn remove<T: Decomposable<TParts>>(&mut self, t: T) -> bool {
+        self.trie.remove(terminated(t))
+    }
+
+    /// iterates over the decomposed parts of every stored element, without the terminator
+    ///
+    /// elements are ordered as in `Trie::iter`, with the terminator in its reserved position among
+    /// the parts
+    pub fn iter(&self) -> impl Iterator<Item=Vec<TParts>> + '_ where TParts: Clone {
+        self.trie.iter().map(unterminated)
+    }
+
+    /// iterates over every stored element starting with `prefix`, in the same order as `iter`
+    pub fn keys_with_prefix<T: Decomposable<TParts>>(&self, prefix: T) -> impl Iterator<Item=Vec<TParts>> + '_ where TParts: Clone {
+        self.trie.keys_with_prefix(parts(prefix)).map(unterminated)
+    }
+
+    /// number of stored elements starting with `prefix`
+    pub fn count_prefix<T: Decomposable<TParts>>(&self, prefix: T) -> usize {
+        self.trie.count_prefix(parts(prefix))
+    }
+}
+
+impl<TParts, A: Alphabet<TParts>, T: Decomposable<TParts>> Extend<T> for TerminatedTrie<TParts, A> {
+    fn extend<I: IntoIterator<Item=T>>(&mut self, iter: I) {
+        for t in iter {
+            self.insert(t);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use alloc::vec::Vec;
+use core::mem;
+
+use super::{Alphabet, Decomposable, FnAlphabet};
+use super::radix_tree::{checked_index, kept, Iter, Node};
+
+/// A generic tree based map from decomposed keys to values
+///
+/// shares the structure of `Trie`, with each stored key holding its value at the node where the
+/// key's decomposition ends.
+///
+/// # Examples
+///
+/// ```
+/// let mut map = TrieMap::new(AsciiLower);
+/// assert_eq!(map.insert("asd".to_string(), 1), None);
+/// assert_eq!(map.insert("asd".to_string(), 2), Some(1));
+/// assert_eq!(map.get("asd".to_string()), Some(&2));
+/// ```
+#[derive(Clone)]
+pub struct TrieMap<TParts, V, A: Alphabet<TParts>> {
+    pub(crate) root: Node<TParts, V>,
+    alphabet: A,
+    alphabet_size: usize,
+    count: usize,
+}
+
+impl<TParts, V, F: Fn(&TParts) -> usize> TrieMap<TParts, V, FnAlphabet<F>> {
+    /// creates an empty map over an alphabet of `alphabet_size` symbols given by `index_fn`, see
+    /// `Trie::from_fn`
+    pub fn from_fn(index_fn: F, alphabet_size: usize) -> TrieMap<TParts, V, FnAlphabet<F>> {
+        TrieMap::new(FnAlphabet::new(index_fn, alphabet_size))
+    }
+}
+
+impl<TParts, V, A: Alphabet<TParts>> TrieMap<TParts, V, A> {
+    /// creates an empty map over `alphabet`
+    ///
+    /// every part is checked to be mapped into the alphabet as it is inserted or queried, panicking
+    /// on an index out of range. as with `Trie::new`, keys are reconstructed from the first
+    /// inserted part of every symbol
+    pub fn new(alphabet: A) -> TrieMap<TParts, V, A> {
+        let new_node = Node::new_empty();
+        let alphabet_size = alphabet.size();
+        TrieMap { root: new_node, alphabet, alphabet_size, count: 0 }
+    }
+
+    /// creates a map over `alphabet` holding every key value pair of `iter`, later pairs overwriting
+    /// earlier ones
+    pub fn from_iter_with<T: Decomposable<TParts>, I: IntoIterator<Item=(T, V)>>(alphabet: A, iter: I) -> TrieMap<TParts, V, A> {
+        let mut map = TrieMap::new(alphabet);
+        map.extend(iter);
+        map
+    }
+
+    /// number of keys stored in the map
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// removes every key, keeping the alphabet for reuse
+    pub fn clear(&mut self) {
+        self.root = Node::new_empty();
+        self.count = 0;
+    }
+
+    /// releases the spare capacity of the map, see `Trie::shrink_to_fit`
+    pub fn shrink_to_fit(&mut self) {
+        self.root.shrink_to_fit();
+    }
+
+    /// associates `value` with `key`, returns the value previously associated with it
+    ///
+    /// a value already there is replaced, see `insert_with_policy` for keeping it instead
+    pub fn insert<T: Decomposable<TParts>>(&mut self, key: T, value: V) -> Option<V> {
+        let index_fn = checked_index(&self.alphabet, self.alphabet_size);
+        let mut it = kept(key.decompose(), &self.alphabet).inspect(|part| { index_fn(part); }).peekable();
+        let old = self.root.insert(&mut it, value, &index_fn, self.alphabet_size);
+        if old.is_none() {
+            self.count += 1;
+        }
+        old
+    }
+
+    /// associates `value` with `key` unless `policy` rejects replacing a value already there
+    ///
+    /// a key without a value always takes `value`, returning `Ok(None)`. a key with one returns
+    /// `Ok` of the replaced value under `InsertPolicy::Replace`, as `insert` does, `Ok` of `value`
+    /// handed back under `InsertPolicy::Keep`, and `Err` of `value` under `InsertPolicy::Error`
+    pub fn insert_with_policy<T: Decomposable<TParts>>(&mut self, key: T, value: V, policy: InsertPolicy) -> Result<Option<V>, V> {
+        let index_fn = checked_index(&self.alphabet, self.alphabet_size);
+        let mut it = kept(key.decompose(), &self.alphabet).inspect(|part| { index_fn(part); }).peekable();
+        let update = |stored: &mut V, value| match policy {
+            InsertPolicy::Replace => mem::replace(stored, value),
+            InsertPolicy::Keep | InsertPolicy::Error => value,
+        };
+        match self.root.upsert(&mut it, value, update, &index_fn, self.alphabet_size, 1) {
+            None => {
+                self.count += 1;
+                Ok(None)
+            }
+            Some(value) if policy == InsertPolicy::Error => Err(value),
+            old => Ok(old),
+        }
+    }
+
+    pub fn get<T: Decomposable<TParts>>(&self, key: T) -> Option<&V> {
+        self.root.get(kept(key.decompose(), &self.alphabet), &checked_index(&self.alphabet, self.alphabet_size))
+    }
+
+    pub fn get_mut<T: Decomposable<TParts>>(&mut self, key: T) -> Option<&mut V> {
+        let index_fn = checked_index(&self.alphabet, self.alphabet_size);
+        self.root.get_mut(&mut kept(key.decompose(), &self.alphabet).map(|part| index_fn(&part)).peekable(), &index_fn)
+    }
+
+    /// applies `f` to the value of `key` in place, returns whether the key had a value
+    pub fn update<T: Decomposable<TParts>, F: FnOnce(&mut V)>(&mut self, key: T, f: F) -> bool {
+        match self.get_mut(key) {
+            Some(value) => {
+                f(value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// the entry of `key`, to be read, updated or filled in place
+    ///
+    /// the key is decomposed up front, a vacant entry holding on to its parts until a value is
+    /// inserted
+    pub fn entry<T: Decomposable<TParts>>(&mut self, key: T) -> Entry<'_, TParts, V, A> {
+        let parts = kept(key.decompose(), &self.alphabet).collect::<Vec<_>>();
+        if self.root.get(parts.iter(), &checked_index(&self.alphabet, self.alphabet_size)).is_none() {
+            return Entry::Vacant(VacantEntry { map: self, parts });
+        }
+        let index_fn = checked_index(&self.alphabet, self.alphabet_size);
+        let value = self.root.get_mut(&mut parts.iter().map(&index_fn).peekable(), &index_fn).unwrap();
+        Entry::Occupied(OccupiedEntry { value })
+    }
+
+    pub fn contains_key<T: Decomposable<TParts>>(&self, key: T) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// removes `key` from the map, returns the value that was associated with it
+    pub fn remove<T: Decomposable<TParts>>(&mut self, key: T) -> Option<V> {
+        let removed = self.root.remove(&mut kept(key.decompose(), &self.alphabet).peekable(), &checked_index(&self.alphabet, self.alphabet_size));
+        if removed.is_some() {
+            self.count -= 1;
+        }
+        removed
+    }
+
+    /// iterates over the decomposed parts of every stored key along with its value, in the same
+    /// order as `Trie::iter`
+    pub fn iter(&self) -> impl Iterator<Item=(Vec<TParts>, &V)> + '_ where TParts: Clone {
+        Iter::new(Vec::new(), &self.root)
+    }
+}
+
+/// what `TrieMap::insert_with_policy` does with a key already holding a value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InsertPolicy {
+    /// the new value takes the place of the stored one, which is returned. the policy of `insert`
+    #[default]
+    Replace,
+    /// the stored value stays, the new value is returned
+    Keep,
+    /// the stored value stays, the new value is returned as an error
+    Error,
+}
+
+/// the entry of a key in a `TrieMap`, see `TrieMap::entry`
+pub enum Entry<'a, TParts, V, A: Alphabet<TParts>> {
+    Occupied(OccupiedEntry<'a, V>),
+    Vacant(VacantEntry<'a, TParts, V, A>),
+}
+
+impl<'a, TParts, V, A: Alphabet<TParts>> Entry<'a, TParts, V, A> {
+    /// the value of the key, inserting `default` first if it has none
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// the value of the key, inserting the value returned by `default` first if it has none
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// the value of the key, inserting `V::default()` first if it has none
+    pub fn or_default(self) -> &'a mut V where V: Default {
+        self.or_insert_with(V::default)
+    }
+
+    /// hands the value of the key to `f` if it has one
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Entry<'a, TParts, V, A> {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            entry => entry,
+        }
+    }
+}
+
+/// the entry of a key holding a value, borrowing the value
+pub struct OccupiedEntry<'a, V> {
+    value: &'a mut V,
+}
+
+impl<'a, V> OccupiedEntry<'a, V> {
+    pub fn get(&self) -> &V {
+        self.value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        self.value
+    }
+
+    /// the value, borrowed for as long as the map was
+    pub fn into_mut(self) -> &'a mut V {
+        self.value
+    }
+
+    /// replaces the value, returns the one replaced
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.value, value)
+    }
+}
+
+/// the entry of a key without a value, holding the decomposed key until one is inserted
+pub struct VacantEntry<'a, TParts, V, A: Alphabet<TParts>> {
+    map: &'a mut TrieMap<TParts, V, A>,
+    parts: Vec<TParts>,
+}
+
+impl<'a, TParts, V, A: Alphabet<TParts>> VacantEntry<'a, TParts, V, A> {
+    /// the decomposed parts of the key
+    pub fn key(&self) -> &[TParts] {
+        &self.parts
+    }
+
+    /// associates `value` with the key, returns the value as stored in the map
+    pub fn insert(self, value: V) -> &'a mut V {
+        let VacantEntry { map, parts } = self;
+        let index_fn = checked_index(&map.alphabet, map.alphabet_size);
+        let positions = parts.iter().map(&index_fn).collect::<Vec<_>>();
+        map.root.insert(&mut parts.into_iter().peekable(), value, &index_fn, map.alphabet_size);
+        map.count += 1;
+        map.root.get_mut(&mut positions.into_iter().peekable(), &index_fn).unwrap()
+    }
+}
+
+impl<TParts, V, A: Alphabet<TParts>, T: Decomposable<TParts>> Extend<(T, V)> for TrieMap<TParts, V, A> {
+    fn extend<I: IntoIterator<Item=(T, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
@@ -0,0 +1,7 @@
+use triez::{AsciiLower, Trie};
+
+fn main() {
+    let mut trie = Trie::new(AsciiLower);
+    trie.insert("asd");
+    trie.insert(vec![1_u8, 2, 3]);
+}
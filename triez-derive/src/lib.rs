@@ -0,0 +1,152 @@
+//! `#[derive(Decomposable)]` for the `Decomposable` trait of triez, re-exported by triez under its
+//! `derive` feature
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Type};
+
+/// Derives `Decomposable` by chaining the decompositions of every field in declaration order
+///
+/// every field must itself be `Decomposable` into the same parts, `u8` unless set with
+/// `#[decomposable(parts = Type)]`. structs decompose into their fields without allocating, enums
+/// decompose into the index of the variant, converted with `From<u8>`, followed by the fields of
+/// the variant, behind a boxed iterator.
+///
+/// # Examples
+///
+/// ```
+/// #[derive(Decomposable)]
+/// struct Key {
+///     region: u16,
+///     id: u32,
+/// }
+///
+/// let mut trie = Trie::new(Bytes);
+/// trie.insert(Key { region: 1, id: 7 });
+/// ```
+#[proc_macro_derive(Decomposable, attributes(decomposable))]
+pub fn derive_decomposable(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let parts = parts_type(&input)?;
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+
+    let (iterator, body, field_types) = match &input.data {
+        Data::Struct(data) => {
+            let types = field_types(&data.fields);
+            let bindings = bindings(&data.fields);
+            let pattern = pattern(quote!(#name), &data.fields, &bindings);
+            let iterator = chained_type(&types, &parts);
+            let chained = chained(&bindings, &parts);
+            (iterator, quote! { let #pattern = self; #chained }, types)
+        }
+        Data::Enum(data) => {
+            if data.variants.len() > 256 {
+                return Err(Error::new_spanned(&input.ident, "Decomposable can only be derived for enums of at most 256 variants"));
+            }
+            let mut types = Vec::new();
+            let mut arms = Vec::new();
+            for (index, variant) in data.variants.iter().enumerate() {
+                let index = index as u8;
+                let variant_name = &variant.ident;
+                let bindings = bindings(&variant.fields);
+                let pattern = pattern(quote!(#name::#variant_name), &variant.fields, &bindings);
+                let chained = chained(&bindings, &parts);
+                arms.push(quote! {
+                    #pattern => ::triez::__private::Box::new(
+                        ::core::iter::once(<#parts as ::core::convert::From<u8>>::from(#index)).chain(#chained)
+                    ),
+                });
+                types.extend(field_types(&variant.fields));
+            }
+            let where_clause = generics.make_where_clause();
+            where_clause.predicates.push(syn::parse_quote!(#parts: ::core::convert::From<u8> + 'static));
+            for ty in &types {
+                where_clause.predicates.push(syn::parse_quote!(<#ty as ::triez::Decomposable<#parts>>::Iterator: 'static));
+            }
+            let iterator = quote!(::triez::__private::Box<dyn ::core::iter::Iterator<Item=#parts>>);
+            (iterator, quote! { match self { #(#arms)* } }, types)
+        }
+        Data::Union(_) => return Err(Error::new_spanned(&input.ident, "Decomposable can not be derived for unions")),
+    };
+
+    let where_clause = generics.make_where_clause();
+    for ty in &field_types {
+        where_clause.predicates.push(syn::parse_quote!(#ty: ::triez::Decomposable<#parts>));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::triez::Decomposable<#parts> for #name #ty_generics #where_clause {
+            type Iterator = #iterator;
+
+            fn decompose(self) -> Self::Iterator {
+                #body
+            }
+        }
+    })
+}
+
+/// the parts set with `#[decomposable(parts = Type)]`, `u8` otherwise
+fn parts_type(input: &DeriveInput) -> syn::Result<Type> {
+    let mut parts = syn::parse_quote!(u8);
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("decomposable")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("parts") {
+                parts = meta.value()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported decomposable attribute, expected `parts = Type`"))
+            }
+        })?;
+    }
+    Ok(parts)
+}
+
+fn field_types(fields: &Fields) -> Vec<Type> {
+    fields.iter().map(|field| field.ty.clone()).collect()
+}
+
+fn bindings(fields: &Fields) -> Vec<syn::Ident> {
+    (0..fields.len()).map(|i| format_ident!("field_{}", i)).collect()
+}
+
+/// a pattern destructuring `path` into `bindings`
+fn pattern(path: TokenStream, fields: &Fields, bindings: &[syn::Ident]) -> TokenStream {
+    match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|field| &field.ident);
+            quote!(#path { #(#names: #bindings),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#bindings),*)),
+        Fields::Unit => quote!(#path),
+    }
+}
+
+/// the type of the decompositions of `types` chained one after the other
+fn chained_type(types: &[Type], parts: &Type) -> TokenStream {
+    let mut types = types.iter();
+    let first = match types.next() {
+        Some(ty) => quote!(<#ty as ::triez::Decomposable<#parts>>::Iterator),
+        None => return quote!(::core::iter::Empty<#parts>),
+    };
+    types.fold(first, |chained, ty| {
+        quote!(::core::iter::Chain<#chained, <#ty as ::triez::Decomposable<#parts>>::Iterator>)
+    })
+}
+
+/// the decompositions of `bindings` chained one after the other
+fn chained(bindings: &[syn::Ident], parts: &Type) -> TokenStream {
+    let mut bindings = bindings.iter();
+    let first = match bindings.next() {
+        Some(binding) => quote!(::triez::Decomposable::<#parts>::decompose(#binding)),
+        None => return quote!(::core::iter::empty::<#parts>()),
+    };
+    bindings.fold(first, |chained, binding| {
+        quote!(::core::iter::Iterator::chain(#chained, ::triez::Decomposable::<#parts>::decompose(#binding)))
+    })
+}
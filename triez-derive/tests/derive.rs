@@ -0,0 +1,73 @@
+use triez::Decomposable as _;
+use triez::{Bytes, Trie};
+use triez_derive::Decomposable;
+
+#[derive(Decomposable)]
+struct Key {
+    region: u16,
+    id: u32,
+}
+
+#[derive(Decomposable)]
+struct Pair(u16, [u8; 2]);
+
+#[derive(Decomposable)]
+struct Unit;
+
+#[derive(Decomposable)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+#[derive(Decomposable)]
+#[decomposable(parts = char)]
+struct Name {
+    first: String,
+    last: String,
+}
+
+#[derive(Decomposable)]
+enum Shape {
+    Point,
+    Circle { radius: u16 },
+    Rect(u16, u16),
+}
+
+#[test]
+fn test_struct_fields_in_order() {
+    let parts = Key { region: 0x0102, id: 0x0304_0506 }.decompose().collect::<Vec<_>>();
+    assert_eq!(parts, vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(Pair(0x0a0b, [1, 2]).decompose().collect::<Vec<_>>(), vec![10, 11, 1, 2]);
+    assert_eq!(Unit.decompose().count(), 0);
+    assert_eq!(Wrapper { inner: 7_u16 }.decompose().collect::<Vec<_>>(), vec![0, 7]);
+}
+
+#[test]
+fn test_custom_parts() {
+    let name = Name { first: "ab".to_string(), last: "cd".to_string() };
+    assert_eq!(name.decompose().collect::<String>(), "abcd");
+}
+
+#[test]
+fn test_enum_variant_index_first() {
+    assert_eq!(Shape::Point.decompose().collect::<Vec<_>>(), vec![0]);
+    assert_eq!(Shape::Circle { radius: 3 }.decompose().collect::<Vec<_>>(), vec![1, 0, 3]);
+    assert_eq!(Shape::Rect(1, 2).decompose().collect::<Vec<_>>(), vec![2, 0, 1, 0, 2]);
+}
+
+#[test]
+fn test_trie_of_derived_keys() {
+    let mut trie = Trie::new(Bytes);
+
+    trie.insert(Key { region: 1, id: 7 });
+    trie.insert(Key { region: 1, id: 8 });
+    assert!(trie.contains(Key { region: 1, id: 7 }));
+    assert!(!trie.contains(Key { region: 2, id: 7 }));
+    assert!(trie.starts_with(&[0, 1][..]));
+}
+
+#[test]
+fn test_ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
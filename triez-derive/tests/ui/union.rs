@@ -0,0 +1,9 @@
+use triez_derive::Decomposable;
+
+#[derive(Decomposable)]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use triez_derive::Decomposable;
+
+#[derive(Decomposable)]
+#[decomposable(part = u8)]
+struct Key {
+    id: u32,
+}
+
+fn main() {}